@@ -1,2 +1,648 @@
 #![allow(unused, dead_code)]
-pub struct Breeder {}
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::cost::{PokemonBreedCosts, PokemonBreedTreeCost};
+use crate::history::History;
+use crate::inventory::{Inventory, InventoryId, InventorySlotReport};
+use crate::pokedex::Pokedex;
+use crate::shopping::ShoppingList;
+use crate::target::PokemonBreedTarget;
+use crate::tree::{
+    PokemonBreedNodeState, PokemonBreedRevalidation, PokemonBreedStep, PokemonBreedTree,
+    PokemonBreedTreeError, PokemonBreedTreeEvent, PokemonBreedTreeNode, Position,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreederSettings {
+    // Use the pokemon of the inventory in the plan instead of buying or breeding them
+    pub use_inventory: bool,
+    // Buy the pokemon that are cheaper on the market than to breed
+    pub make_or_buy: bool,
+    // Most pokemon that can be kept at once while following the steps
+    pub storage_limit: Option<usize>,
+}
+
+impl Default for BreederSettings {
+    fn default() -> Self {
+        BreederSettings {
+            use_inventory: true,
+            make_or_buy: false,
+            storage_limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreederError {
+    // A plan needs a target first
+    NoTarget,
+    // The operation works on the current plan, and there isn't one
+    NoPlan,
+    UnknownCheckpoint(String),
+    Tree(PokemonBreedTreeError),
+}
+
+impl std::fmt::Display for BreederError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BreederError::NoTarget => write!(f, "There is no target to plan"),
+            BreederError::NoPlan => write!(f, "There is no plan yet"),
+            BreederError::UnknownCheckpoint(name) => {
+                write!(f, "There is no checkpoint named {}", name)
+            }
+            BreederError::Tree(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for BreederError {}
+
+impl From<PokemonBreedTreeError> for BreederError {
+    fn from(error: PokemonBreedTreeError) -> Self {
+        BreederError::Tree(error)
+    }
+}
+
+// A planning session: what to breed, the species data and prices to plan it with, what the user
+// owns, and the plan made from all of it. Changing any of them doesn't touch the current plan
+// until it's planned again.
+#[derive(Debug, Default)]
+pub struct Breeder {
+    pub target: Option<PokemonBreedTarget>,
+    pub pokedex: Pokedex,
+    pub costs: PokemonBreedCosts,
+    pub inventory: Inventory,
+    pub settings: BreederSettings,
+    state: BreederState,
+    // Every plan built and every edit made to it
+    history: History<BreederState>,
+    subscribers: Vec<Sender<PokemonBreedTreeEvent>>,
+}
+
+// A copy of the session starts without subscribers, its changes aren't the ones of the original
+impl Clone for Breeder {
+    fn clone(&self) -> Self {
+        Breeder {
+            target: self.target.clone(),
+            pokedex: self.pokedex.clone(),
+            costs: self.costs.clone(),
+            inventory: self.inventory.clone(),
+            settings: self.settings.clone(),
+            state: self.state.clone(),
+            history: self.history.clone(),
+            subscribers: vec![],
+        }
+    }
+}
+
+// The plan and the inventory pokemon used in it, saved together in the history so an undo
+// puts back both
+#[derive(Debug, Clone, Default)]
+pub struct BreederState {
+    pub plan: Option<PokemonBreedTree>,
+    pub slot_report: Option<InventorySlotReport>,
+}
+
+impl Breeder {
+    pub fn new(pokedex: Pokedex, costs: PokemonBreedCosts) -> Breeder {
+        Breeder {
+            pokedex,
+            costs,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_target(&mut self, target: PokemonBreedTarget) {
+        self.target = Some(target);
+    }
+
+    // Builds a new plan for the target, replacing the current one. Inventory pokemon used in the
+    // plan are reserved for it instead of being used up, and the ones reserved for the plan it
+    // replaces are released.
+    pub fn build_plan(&mut self) -> Result<&PokemonBreedTree, BreederError> {
+        let target = self.target.as_ref().ok_or(BreederError::NoTarget)?;
+
+        let mut tree = PokemonBreedTree::plan(&target.node, &target.ivs_map)?;
+        tree.fill_hatch_species(&self.pokedex)?;
+
+        self.history.record("Build plan", &self.state);
+        self.release_slotted();
+        self.state.slot_report = if self.settings.use_inventory {
+            Some(self.inventory.slot_into(&mut tree, &self.costs))
+        } else {
+            None
+        };
+        if self.settings.make_or_buy {
+            self.costs.make_or_buy(&mut tree);
+        }
+        tree.validate();
+
+        let before = self.state.plan.replace(tree);
+        self.notify_replaced(before);
+
+        Ok(self.state.plan.as_ref().expect("Should exist"))
+    }
+
+    pub fn plan(&self) -> Option<&PokemonBreedTree> {
+        self.state.plan.as_ref()
+    }
+
+    // Edits the current plan so the edit can be undone. Nothing is changed when the edit fails.
+    // This is the only way to change the plan in place, so every edit is in the history.
+    pub fn edit_plan<F, R>(&mut self, description: &str, edit: F) -> Result<R, BreederError>
+    where
+        F: FnOnce(&mut PokemonBreedTree) -> Result<R, PokemonBreedTreeError>,
+    {
+        let before = self.state.clone();
+        let plan = self.state.plan.as_mut().ok_or(BreederError::NoPlan)?;
+
+        match edit(plan) {
+            Ok(result) => {
+                self.history.record(description, &before);
+                self.notify_edit();
+                Ok(result)
+            }
+            Err(error) => {
+                self.state = before;
+                Err(error.into())
+            }
+        }
+    }
+
+    // Undoes the last plan built or edit made, and returns what it was. The inventory pokemon
+    // reserved for the plan go back to the ones of the plan put back.
+    pub fn undo(&mut self) -> Option<String> {
+        let before = self.snapshot();
+        self.release_slotted();
+        let description = self.history.undo(&mut self.state);
+        self.reserve_slotted();
+        self.notify_replaced(before);
+
+        description
+    }
+
+    pub fn redo(&mut self) -> Option<String> {
+        let before = self.snapshot();
+        self.release_slotted();
+        let description = self.history.redo(&mut self.state);
+        self.reserve_slotted();
+        self.notify_replaced(before);
+
+        description
+    }
+
+    // The pokemon is checked against the nodes around it, see PokemonBreedTree::insert_pokemon
+    pub fn insert_pokemon(
+        &mut self,
+        position: Position,
+        pokemon_node: PokemonBreedTreeNode,
+    ) -> Result<PokemonBreedRevalidation, BreederError> {
+        self.edit_plan("Insert pokemon", |plan| {
+            Ok(plan.insert_pokemon(position, pokemon_node))
+        })
+    }
+
+    pub fn assign_genders(&mut self) -> Result<(), BreederError> {
+        self.edit_plan("Assign genders", |plan| {
+            plan.assign_genders();
+            Ok(())
+        })
+    }
+
+    pub fn set_node_state(
+        &mut self,
+        position: Position,
+        state: PokemonBreedNodeState,
+    ) -> Result<(), BreederError> {
+        self.edit_plan("Update progress", |plan| {
+            plan.set_node_state(position, state)
+        })
+    }
+
+    // Events are sent for every change of the plan from now on, until the receiver is dropped.
+    // Edits send the events of the tree methods they call, a plan that is built or put back
+    // sends how it differs from the one it replaces.
+    pub fn subscribe(&mut self) -> Receiver<PokemonBreedTreeEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+
+        receiver
+    }
+
+    // A copy of the plan to compare the one put back against, only needed when someone listens
+    fn snapshot(&self) -> Option<PokemonBreedTree> {
+        if self.subscribers.is_empty() {
+            None
+        } else {
+            self.state.plan.clone()
+        }
+    }
+
+    // Sends the changes made by the tree methods of an edit
+    fn notify_edit(&mut self) {
+        let events = self
+            .state
+            .plan
+            .as_mut()
+            .map(|plan| plan.take_events())
+            .unwrap_or_default();
+        self.send(events);
+    }
+
+    // Sends how the plan that was built or put back differs from the one it replaced
+    fn notify_replaced(&mut self, before: Option<PokemonBreedTree>) {
+        // What the new plan journaled is how it was made, not a change of the current plan
+        if let Some(plan) = self.state.plan.as_mut() {
+            plan.take_events();
+        }
+        if self.subscribers.is_empty() {
+            return;
+        }
+
+        let events =
+            PokemonBreedTree::replacement_events(before.as_ref(), self.state.plan.as_ref());
+        self.send(events);
+    }
+
+    fn send(&mut self, events: Vec<PokemonBreedTreeEvent>) {
+        self.subscribers.retain(|subscriber| {
+            events
+                .iter()
+                .all(|event| subscriber.send(event.clone()).is_ok())
+        });
+    }
+
+    pub fn history(&self) -> &History<BreederState> {
+        &self.history
+    }
+
+    pub fn checkpoint(&mut self, name: &str) {
+        self.history.checkpoint(name, &self.state);
+    }
+
+    pub fn restore_checkpoint(&mut self, name: &str) -> Result<(), BreederError> {
+        let before = self.snapshot();
+        self.release_slotted();
+        let restored = self.history.restore(name, &mut self.state);
+        self.reserve_slotted();
+        if restored {
+            self.notify_replaced(before);
+            Ok(())
+        } else {
+            Err(BreederError::UnknownCheckpoint(name.to_string()))
+        }
+    }
+
+    pub fn slot_report(&self) -> Option<&InventorySlotReport> {
+        self.state.slot_report.as_ref()
+    }
+
+    fn slotted_ids(&self) -> Vec<InventoryId> {
+        self.state
+            .slot_report
+            .iter()
+            .flat_map(|report| report.slotted.iter().map(|(_, id)| *id))
+            .collect()
+    }
+
+    fn reserve_slotted(&mut self) {
+        for id in self.slotted_ids() {
+            self.inventory.reserve(id);
+        }
+    }
+
+    fn release_slotted(&mut self) {
+        for id in self.slotted_ids() {
+            self.inventory.release(id);
+        }
+    }
+
+    fn current_plan(&self) -> Result<&PokemonBreedTree, BreederError> {
+        self.state.plan.as_ref().ok_or(BreederError::NoPlan)
+    }
+
+    // The positions of the current plan with breeding errors. Every edit keeps them up to date,
+    // so nothing is validated again here.
+    pub fn validate(&self) -> Result<&[Position], BreederError> {
+        Ok(&self.current_plan()?.breed_errors)
+    }
+
+    pub fn cost(&self) -> Result<PokemonBreedTreeCost, BreederError> {
+        Ok(self.costs.tree_cost(self.current_plan()?))
+    }
+
+    pub fn remaining_cost(&self) -> Result<PokemonBreedTreeCost, BreederError> {
+        Ok(self.costs.remaining_cost(self.current_plan()?))
+    }
+
+    pub fn shopping_list(&self) -> Result<ShoppingList, BreederError> {
+        Ok(ShoppingList::from_tree(
+            self.current_plan()?,
+            &self.costs,
+            &self.pokedex,
+        ))
+    }
+
+    // The steps left, kept within the storage limit when there is one
+    pub fn steps(&self) -> Result<Vec<PokemonBreedStep>, BreederError> {
+        let plan = self.current_plan()?;
+
+        match self.settings.storage_limit {
+            Some(storage_limit) => Ok(plan.schedule_steps(storage_limit)?),
+            None => Ok(plan.step_order()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        cost::PokemonBreedCosts,
+        fixtures::{charmander, charmander_breeder, owned_charmander},
+        pokedex::Pokedex,
+        pokemon::{Pokemon, PokemonEggGroup, PokemonGender, PokemonIv, PokemonType},
+        tree::{PokemonBreedNodeState, PokemonBreedTreeError, PokemonBreedTreeEvent, Position},
+    };
+
+    use super::{Breeder, BreederError};
+
+    #[test]
+    fn breeder_test() {
+        let mut breeder = Breeder::new(
+            Pokedex::new(vec![charmander()]),
+            PokemonBreedCosts::default(),
+        );
+        assert_eq!(breeder.build_plan().err(), Some(BreederError::NoTarget));
+        assert_eq!(breeder.cost(), Err(BreederError::NoPlan));
+
+        let mut breeder = charmander_breeder(&[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]);
+        breeder
+            .inventory
+            .add(owned_charmander(&[PokemonIv::Attack, PokemonIv::Speed]));
+
+        let plan = breeder.build_plan().expect("Should be a valid target");
+        assert_eq!(plan.pokemon_nodes.len(), 5);
+        assert!(plan.owned.contains(&Position::new(1, 0)));
+        assert!(plan.breed_errors.is_empty());
+        // The inventory is only used up when the plan is followed
+        assert_eq!(breeder.inventory.len(), 1);
+        assert_eq!(
+            breeder.slot_report().expect("Should exist").slotted.len(),
+            1
+        );
+
+        let cost = breeder.cost().expect("Should have a plan");
+        assert_eq!(
+            breeder.shopping_list().expect("Should have a plan").total(),
+            cost.total()
+        );
+        assert_eq!(breeder.steps().expect("Should have a plan").len(), 4);
+
+        // The owned pokemon waits while its partner is bred from two more pokemon
+        breeder.settings.storage_limit = Some(2);
+        assert_eq!(
+            breeder.steps(),
+            Err(BreederError::Tree(PokemonBreedTreeError::NotEnoughStorage(
+                3
+            )))
+        );
+    }
+
+    #[test]
+    fn breeder_inventory_reservation_test() {
+        let mut breeder = charmander_breeder(&[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]);
+        let id = breeder
+            .inventory
+            .add(owned_charmander(&[PokemonIv::Attack, PokemonIv::Speed]));
+
+        breeder.build_plan().expect("Should be a valid target");
+        assert!(breeder.inventory.is_reserved(id));
+        // Planning again releases the pokemon of the replaced plan before slotting
+        breeder.build_plan().expect("Should be a valid target");
+        assert_eq!(
+            breeder.slot_report().expect("Should exist").slotted,
+            vec![(Position::new(1, 0), id)]
+        );
+
+        // Another plan made from the same inventory can't use the reserved pokemon
+        let mut other = charmander_breeder(&[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]);
+        other.inventory = breeder.inventory.clone();
+        other.build_plan().expect("Should be a valid target");
+        assert!(other
+            .slot_report()
+            .expect("Should exist")
+            .slotted
+            .is_empty());
+        assert!(other.inventory.take(|_| true).is_none());
+    }
+
+    #[test]
+    fn breeder_history_test() {
+        let mut breeder = charmander_breeder(&[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]);
+        assert_eq!(
+            breeder.edit_plan("Swap parents", |plan| plan
+                .swap_parents(Position::new(1, 1))),
+            Err(BreederError::NoPlan)
+        );
+
+        breeder.build_plan().expect("Should be a valid target");
+        breeder.checkpoint("generated");
+        let generated = breeder.plan().expect("Should exist").pokemon_nodes.clone();
+
+        breeder
+            .edit_plan("Swap parents", |plan| {
+                plan.swap_parents(Position::new(1, 1))
+            })
+            .expect("Should be a pair");
+        // A failed edit leaves the plan as it was and isn't recorded
+        assert!(breeder
+            .edit_plan("Swap parents", |plan| {
+                plan.swap_parents(Position::new(2, 0))
+            })
+            .is_err());
+        assert_eq!(
+            breeder.history().undo_descriptions(),
+            vec!["Build plan", "Swap parents"]
+        );
+
+        breeder
+            .restore_checkpoint("generated")
+            .expect("Should exist");
+        assert_eq!(
+            breeder.plan().expect("Should exist").pokemon_nodes,
+            generated
+        );
+        assert_eq!(
+            breeder.restore_checkpoint("final"),
+            Err(BreederError::UnknownCheckpoint("final".to_string()))
+        );
+
+        assert_eq!(breeder.undo().as_deref(), Some("Restore generated"));
+        assert_eq!(breeder.undo().as_deref(), Some("Swap parents"));
+        assert_eq!(breeder.undo().as_deref(), Some("Build plan"));
+        assert!(breeder.plan().is_none());
+        assert_eq!(breeder.redo().as_deref(), Some("Build plan"));
+        assert_eq!(
+            breeder.plan().expect("Should exist").pokemon_nodes,
+            generated
+        );
+    }
+
+    #[test]
+    fn breeder_history_inventory_test() {
+        let mut breeder = charmander_breeder(&[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]);
+        let id = breeder
+            .inventory
+            .add(owned_charmander(&[PokemonIv::Attack, PokemonIv::Speed]));
+
+        breeder.build_plan().expect("Should be a valid target");
+        breeder.settings.use_inventory = false;
+        breeder.build_plan().expect("Should be a valid target");
+        assert!(breeder.slot_report().is_none());
+        assert!(!breeder.inventory.is_reserved(id));
+
+        // Going back to the first plan puts back the pokemon it used
+        breeder.undo();
+        assert_eq!(
+            breeder.slot_report().expect("Should exist").slotted,
+            vec![(Position::new(1, 0), id)]
+        );
+        assert!(breeder.inventory.is_reserved(id));
+        breeder.undo();
+        assert!(breeder.slot_report().is_none());
+        assert!(!breeder.inventory.is_reserved(id));
+        breeder.redo();
+        assert!(breeder.inventory.is_reserved(id));
+
+        breeder.checkpoint("with inventory");
+        breeder.redo();
+        assert!(!breeder.inventory.is_reserved(id));
+        breeder
+            .restore_checkpoint("with inventory")
+            .expect("Should exist");
+        assert!(breeder.slot_report().is_some());
+        assert!(breeder.inventory.is_reserved(id));
+    }
+
+    #[test]
+    fn breeder_events_test() {
+        let mut breeder = charmander_breeder(&[PokemonIv::Attack, PokemonIv::Speed]);
+        let events = breeder.subscribe();
+
+        breeder.build_plan().expect("Should be a valid target");
+        let inserted = events.try_iter().collect::<Vec<PokemonBreedTreeEvent>>();
+        assert_eq!(inserted.len(), 3);
+        assert!(inserted
+            .iter()
+            .all(|event| matches!(event, PokemonBreedTreeEvent::NodeInserted(_))));
+
+        breeder
+            .set_node_state(Position::new(1, 0), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        assert_eq!(
+            events.try_iter().collect::<Vec<PokemonBreedTreeEvent>>(),
+            vec![PokemonBreedTreeEvent::ProgressUpdated(
+                Position::new(1, 0),
+                PokemonBreedNodeState::Acquired
+            )]
+        );
+
+        // A genderless pokemon can only breed with a Ditto
+        let mut node =
+            breeder.plan().expect("Should exist").pokemon_nodes[&Position::new(1, 1)].clone();
+        node.pokemon = Some(Pokemon {
+            number: 81,
+            name: "Magnemite".to_string(),
+            types: (PokemonType::Electric, Some(PokemonType::Steel)),
+            egg_groups: (PokemonEggGroup::Genderless, None),
+            percentage_male: 0.0,
+        });
+        node.gender = Some(PokemonGender::Genderless);
+        breeder
+            .insert_pokemon(Position::new(1, 1), node)
+            .expect("Should have a plan");
+        let diagnostics = PokemonBreedTreeEvent::DiagnosticsChanged {
+            new_errors: vec![Position::new(1, 0), Position::new(1, 1)],
+            fixed_errors: vec![],
+        };
+        assert_eq!(
+            events.try_iter().collect::<Vec<PokemonBreedTreeEvent>>(),
+            vec![
+                PokemonBreedTreeEvent::NodeChanged(Position::new(1, 1)),
+                PokemonBreedTreeEvent::GenderAssigned(
+                    Position::new(1, 1),
+                    Some(PokemonGender::Genderless)
+                ),
+                diagnostics,
+            ]
+        );
+        assert_eq!(
+            breeder.validate(),
+            Ok([Position::new(1, 0), Position::new(1, 1)].as_slice())
+        );
+
+        breeder.undo();
+        assert!(events
+            .try_iter()
+            .any(|event| matches!(event, PokemonBreedTreeEvent::DiagnosticsChanged { .. })));
+
+        drop(events);
+        breeder.undo();
+        assert!(breeder.subscribers.is_empty());
+    }
+
+    #[test]
+    fn breeder_edit_events_test() {
+        let mut breeder = charmander_breeder(&[PokemonIv::HP, PokemonIv::Attack, PokemonIv::Speed]);
+        breeder.build_plan().expect("Should be a valid target");
+        let events = breeder.subscribe();
+
+        // A copy of the session doesn't send its changes to the subscribers of the original
+        let mut copy = breeder.clone();
+        copy.set_node_state(Position::new(1, 0), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        assert_eq!(events.try_iter().count(), 0);
+
+        // Events come from the tree methods called in the edit, in position order
+        breeder
+            .edit_plan("Swap parents", |plan| {
+                plan.swap_parents(Position::new(0, 0))
+            })
+            .expect("Should keep the final pokemon");
+        let swapped = events.try_iter().collect::<Vec<PokemonBreedTreeEvent>>();
+        let positions = swapped
+            .iter()
+            .filter_map(|event| match event {
+                PokemonBreedTreeEvent::NodeChanged(pos) => Some(*pos),
+                _ => None,
+            })
+            .collect::<Vec<Position>>();
+        assert!(!positions.is_empty());
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(!swapped
+            .iter()
+            .any(|event| matches!(event, PokemonBreedTreeEvent::NodeInserted(_))));
+
+        // A failed edit sends nothing
+        assert!(breeder
+            .edit_plan("Invalid", |plan| plan
+                .set_node_state(Position::new(7, 0), PokemonBreedNodeState::Bred))
+            .is_err());
+        assert_eq!(events.try_iter().count(), 0);
+
+        // A plan put back is compared with the one it replaces, in position order
+        breeder.undo();
+        let undone = events.try_iter().collect::<Vec<PokemonBreedTreeEvent>>();
+        let positions = undone
+            .iter()
+            .filter_map(|event| match event {
+                PokemonBreedTreeEvent::NodeChanged(pos) => Some(*pos),
+                _ => None,
+            })
+            .collect::<Vec<Position>>();
+        assert!(!positions.is_empty());
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -1,6 +1,8 @@
 #![allow(unused, dead_code)]
 use phf::phf_map;
 
+pub const DITTO_NUMBER: u16 = 132;
+
 type GenderlessPokemonEvoTree = (u16, u16, Option<u16>);
 
 const MAGNEMITE_TREE: GenderlessPokemonEvoTree = (81, 82, Some(462));
@@ -40,3 +42,321 @@ pub const GENDERLESS_POKEMON_EVOLUTION_TREE: phf::Map<u16, GenderlessPokemonEvoT
     622u16 => GOLETT_TREE,
     623u16 => GOLETT_TREE,
 };
+
+/**
+ * Maps an evolved pokemon to the pokemon it evolves from and how it evolves.
+ * Baby pokemon that need an incense to hatch are left out, since eggs hatch as the next stage
+ * without one.
+ */
+pub const PRE_EVOLUTIONS: phf::Map<u16, (u16, &'static str)> = phf_map! {
+    2u16 => (1, "Level 16"),
+    3u16 => (2, "Level 32"),
+    5u16 => (4, "Level 16"),
+    6u16 => (5, "Level 36"),
+    8u16 => (7, "Level 16"),
+    9u16 => (8, "Level 36"),
+    11u16 => (10, "Level 7"),
+    12u16 => (11, "Level 10"),
+    14u16 => (13, "Level 7"),
+    15u16 => (14, "Level 10"),
+    17u16 => (16, "Level 18"),
+    18u16 => (17, "Level 36"),
+    20u16 => (19, "Level 20"),
+    22u16 => (21, "Level 20"),
+    24u16 => (23, "Level 22"),
+    25u16 => (172, "High friendship"),
+    26u16 => (25, "Thunder Stone"),
+    28u16 => (27, "Level 22"),
+    30u16 => (29, "Level 16"),
+    31u16 => (30, "Moon Stone"),
+    33u16 => (32, "Level 16"),
+    34u16 => (33, "Moon Stone"),
+    35u16 => (173, "High friendship"),
+    36u16 => (35, "Moon Stone"),
+    38u16 => (37, "Fire Stone"),
+    39u16 => (174, "High friendship"),
+    40u16 => (39, "Moon Stone"),
+    42u16 => (41, "Level 22"),
+    169u16 => (42, "High friendship"),
+    44u16 => (43, "Level 21"),
+    45u16 => (44, "Leaf Stone"),
+    182u16 => (44, "Sun Stone"),
+    47u16 => (46, "Level 24"),
+    49u16 => (48, "Level 31"),
+    51u16 => (50, "Level 26"),
+    53u16 => (52, "Level 28"),
+    55u16 => (54, "Level 33"),
+    57u16 => (56, "Level 28"),
+    59u16 => (58, "Fire Stone"),
+    61u16 => (60, "Level 25"),
+    62u16 => (61, "Water Stone"),
+    186u16 => (61, "Trade holding a King's Rock"),
+    64u16 => (63, "Level 16"),
+    65u16 => (64, "Trade"),
+    67u16 => (66, "Level 28"),
+    68u16 => (67, "Trade"),
+    70u16 => (69, "Level 21"),
+    71u16 => (70, "Leaf Stone"),
+    73u16 => (72, "Level 30"),
+    75u16 => (74, "Level 25"),
+    76u16 => (75, "Trade"),
+    78u16 => (77, "Level 40"),
+    80u16 => (79, "Level 37"),
+    199u16 => (79, "Trade holding a King's Rock"),
+    82u16 => (81, "Level 30"),
+    462u16 => (82, "Level up in a special magnetic field"),
+    85u16 => (84, "Level 31"),
+    87u16 => (86, "Level 34"),
+    89u16 => (88, "Level 38"),
+    91u16 => (90, "Water Stone"),
+    93u16 => (92, "Level 25"),
+    94u16 => (93, "Trade"),
+    208u16 => (95, "Trade holding a Metal Coat"),
+    97u16 => (96, "Level 26"),
+    99u16 => (98, "Level 28"),
+    101u16 => (100, "Level 30"),
+    103u16 => (102, "Leaf Stone"),
+    105u16 => (104, "Level 28"),
+    106u16 => (236, "Level 20 with Attack higher than Defense"),
+    107u16 => (236, "Level 20 with Defense higher than Attack"),
+    237u16 => (236, "Level 20 with equal Attack and Defense"),
+    463u16 => (108, "Level up knowing Rollout"),
+    110u16 => (109, "Level 35"),
+    112u16 => (111, "Level 42"),
+    464u16 => (112, "Trade holding a Protector"),
+    242u16 => (113, "High friendship"),
+    465u16 => (114, "Level up knowing Ancient Power"),
+    117u16 => (116, "Level 32"),
+    230u16 => (117, "Trade holding a Dragon Scale"),
+    119u16 => (118, "Level 33"),
+    121u16 => (120, "Water Stone"),
+    212u16 => (123, "Trade holding a Metal Coat"),
+    124u16 => (238, "Level 30"),
+    125u16 => (239, "Level 30"),
+    466u16 => (125, "Trade holding an Electirizer"),
+    126u16 => (240, "Level 30"),
+    467u16 => (126, "Trade holding a Magmarizer"),
+    130u16 => (129, "Level 20"),
+    134u16 => (133, "Water Stone"),
+    135u16 => (133, "Thunder Stone"),
+    136u16 => (133, "Fire Stone"),
+    196u16 => (133, "High friendship during the day"),
+    197u16 => (133, "High friendship during the night"),
+    470u16 => (133, "Level up near a Moss Rock"),
+    471u16 => (133, "Level up near an Ice Rock"),
+    233u16 => (137, "Trade holding an Up-Grade"),
+    474u16 => (233, "Trade holding a Dubious Disc"),
+    139u16 => (138, "Level 40"),
+    141u16 => (140, "Level 40"),
+    148u16 => (147, "Level 30"),
+    149u16 => (148, "Level 55"),
+    153u16 => (152, "Level 16"),
+    154u16 => (153, "Level 32"),
+    156u16 => (155, "Level 14"),
+    157u16 => (156, "Level 36"),
+    159u16 => (158, "Level 18"),
+    160u16 => (159, "Level 30"),
+    162u16 => (161, "Level 15"),
+    164u16 => (163, "Level 20"),
+    166u16 => (165, "Level 18"),
+    168u16 => (167, "Level 22"),
+    171u16 => (170, "Level 27"),
+    176u16 => (175, "High friendship"),
+    468u16 => (176, "Shiny Stone"),
+    178u16 => (177, "Level 25"),
+    180u16 => (179, "Level 15"),
+    181u16 => (180, "Level 30"),
+    184u16 => (183, "Level 18"),
+    188u16 => (187, "Level 18"),
+    189u16 => (188, "Level 27"),
+    424u16 => (190, "Level up knowing Double Hit"),
+    192u16 => (191, "Sun Stone"),
+    469u16 => (193, "Level up knowing Ancient Power"),
+    195u16 => (194, "Level 20"),
+    430u16 => (198, "Dusk Stone"),
+    429u16 => (200, "Dusk Stone"),
+    205u16 => (204, "Level 31"),
+    472u16 => (207, "Level up holding a Razor Fang during the night"),
+    210u16 => (209, "Level 23"),
+    461u16 => (215, "Level up holding a Razor Claw during the night"),
+    217u16 => (216, "Level 30"),
+    219u16 => (218, "Level 38"),
+    221u16 => (220, "Level 33"),
+    473u16 => (221, "Level up knowing Ancient Power"),
+    224u16 => (223, "Level 25"),
+    229u16 => (228, "Level 24"),
+    232u16 => (231, "Level 25"),
+    247u16 => (246, "Level 30"),
+    248u16 => (247, "Level 55"),
+    253u16 => (252, "Level 16"),
+    254u16 => (253, "Level 36"),
+    256u16 => (255, "Level 16"),
+    257u16 => (256, "Level 36"),
+    259u16 => (258, "Level 16"),
+    260u16 => (259, "Level 36"),
+    262u16 => (261, "Level 18"),
+    264u16 => (263, "Level 20"),
+    266u16 => (265, "Level 7"),
+    267u16 => (266, "Level 10"),
+    268u16 => (265, "Level 7"),
+    269u16 => (268, "Level 10"),
+    271u16 => (270, "Level 14"),
+    272u16 => (271, "Water Stone"),
+    274u16 => (273, "Level 14"),
+    275u16 => (274, "Leaf Stone"),
+    277u16 => (276, "Level 22"),
+    279u16 => (278, "Level 25"),
+    281u16 => (280, "Level 20"),
+    282u16 => (281, "Level 30"),
+    475u16 => (281, "Dawn Stone on a male"),
+    284u16 => (283, "Level 22"),
+    286u16 => (285, "Level 23"),
+    288u16 => (287, "Level 18"),
+    289u16 => (288, "Level 36"),
+    291u16 => (290, "Level 20"),
+    294u16 => (293, "Level 20"),
+    295u16 => (294, "Level 40"),
+    297u16 => (296, "Level 24"),
+    476u16 => (299, "Level up in a special magnetic field"),
+    301u16 => (300, "Moon Stone"),
+    305u16 => (304, "Level 32"),
+    306u16 => (305, "Level 42"),
+    308u16 => (307, "Level 37"),
+    310u16 => (309, "Level 26"),
+    407u16 => (315, "Shiny Stone"),
+    317u16 => (316, "Level 26"),
+    319u16 => (318, "Level 30"),
+    321u16 => (320, "Level 40"),
+    323u16 => (322, "Level 33"),
+    326u16 => (325, "Level 32"),
+    329u16 => (328, "Level 35"),
+    330u16 => (329, "Level 45"),
+    332u16 => (331, "Level 32"),
+    334u16 => (333, "Level 35"),
+    340u16 => (339, "Level 30"),
+    342u16 => (341, "Level 30"),
+    344u16 => (343, "Level 36"),
+    346u16 => (345, "Level 40"),
+    348u16 => (347, "Level 40"),
+    350u16 => (349, "Level up with high Beauty"),
+    354u16 => (353, "Level 37"),
+    356u16 => (355, "Level 37"),
+    477u16 => (356, "Trade holding a Reaper Cloth"),
+    362u16 => (361, "Level 42"),
+    478u16 => (361, "Dawn Stone on a female"),
+    364u16 => (363, "Level 32"),
+    365u16 => (364, "Level 44"),
+    367u16 => (366, "Trade holding a Deep Sea Tooth"),
+    368u16 => (366, "Trade holding a Deep Sea Scale"),
+    372u16 => (371, "Level 30"),
+    373u16 => (372, "Level 50"),
+    375u16 => (374, "Level 20"),
+    376u16 => (375, "Level 45"),
+    388u16 => (387, "Level 18"),
+    389u16 => (388, "Level 32"),
+    391u16 => (390, "Level 14"),
+    392u16 => (391, "Level 36"),
+    394u16 => (393, "Level 16"),
+    395u16 => (394, "Level 36"),
+    397u16 => (396, "Level 14"),
+    398u16 => (397, "Level 34"),
+    400u16 => (399, "Level 15"),
+    402u16 => (401, "Level 10"),
+    404u16 => (403, "Level 15"),
+    405u16 => (404, "Level 30"),
+    409u16 => (408, "Level 30"),
+    411u16 => (410, "Level 30"),
+    413u16 => (412, "Level 20 on a female"),
+    414u16 => (412, "Level 20 on a male"),
+    416u16 => (415, "Level 21 on a female"),
+    419u16 => (418, "Level 26"),
+    421u16 => (420, "Level 25"),
+    423u16 => (422, "Level 30"),
+    426u16 => (425, "Level 28"),
+    428u16 => (427, "High friendship"),
+    432u16 => (431, "Level 38"),
+    435u16 => (434, "Level 34"),
+    437u16 => (436, "Level 33"),
+    444u16 => (443, "Level 24"),
+    445u16 => (444, "Level 48"),
+    448u16 => (447, "High friendship during the day"),
+    450u16 => (449, "Level 34"),
+    452u16 => (451, "Level 40"),
+    454u16 => (453, "Level 37"),
+    457u16 => (456, "Level 31"),
+    460u16 => (459, "Level 40"),
+    496u16 => (495, "Level 17"),
+    497u16 => (496, "Level 36"),
+    499u16 => (498, "Level 17"),
+    500u16 => (499, "Level 36"),
+    502u16 => (501, "Level 17"),
+    503u16 => (502, "Level 36"),
+    505u16 => (504, "Level 20"),
+    507u16 => (506, "Level 16"),
+    508u16 => (507, "Level 32"),
+    510u16 => (509, "Level 20"),
+    512u16 => (511, "Leaf Stone"),
+    514u16 => (513, "Fire Stone"),
+    516u16 => (515, "Water Stone"),
+    518u16 => (517, "Moon Stone"),
+    520u16 => (519, "Level 21"),
+    521u16 => (520, "Level 32"),
+    523u16 => (522, "Level 27"),
+    525u16 => (524, "Level 25"),
+    526u16 => (525, "Trade"),
+    528u16 => (527, "High friendship"),
+    530u16 => (529, "Level 31"),
+    533u16 => (532, "Level 25"),
+    534u16 => (533, "Trade"),
+    536u16 => (535, "Level 25"),
+    537u16 => (536, "Level 36"),
+    541u16 => (540, "Level 20"),
+    542u16 => (541, "High friendship"),
+    544u16 => (543, "Level 22"),
+    545u16 => (544, "Level 30"),
+    547u16 => (546, "Sun Stone"),
+    549u16 => (548, "Sun Stone"),
+    552u16 => (551, "Level 29"),
+    553u16 => (552, "Level 40"),
+    555u16 => (554, "Level 35"),
+    558u16 => (557, "Level 34"),
+    560u16 => (559, "Level 39"),
+    563u16 => (562, "Level 34"),
+    565u16 => (564, "Level 37"),
+    567u16 => (566, "Level 37"),
+    569u16 => (568, "Level 36"),
+    571u16 => (570, "Level 30"),
+    573u16 => (572, "Shiny Stone"),
+    575u16 => (574, "Level 32"),
+    576u16 => (575, "Level 41"),
+    578u16 => (577, "Level 32"),
+    579u16 => (578, "Level 41"),
+    581u16 => (580, "Level 35"),
+    583u16 => (582, "Level 35"),
+    584u16 => (583, "Level 47"),
+    586u16 => (585, "Level 34"),
+    589u16 => (588, "Trade for a Shelmet"),
+    591u16 => (590, "Level 39"),
+    593u16 => (592, "Level 40"),
+    596u16 => (595, "Level 36"),
+    598u16 => (597, "Level 40"),
+    600u16 => (599, "Level 38"),
+    601u16 => (600, "Level 49"),
+    603u16 => (602, "Level 39"),
+    604u16 => (603, "Thunder Stone"),
+    606u16 => (605, "Level 42"),
+    608u16 => (607, "Level 41"),
+    609u16 => (608, "Dusk Stone"),
+    611u16 => (610, "Level 38"),
+    612u16 => (611, "Level 48"),
+    614u16 => (613, "Level 37"),
+    617u16 => (616, "Trade for a Karrablast"),
+    620u16 => (619, "Level 50"),
+    623u16 => (622, "Level 43"),
+    625u16 => (624, "Level 52"),
+    628u16 => (627, "Level 54"),
+    630u16 => (629, "Level 54"),
+    634u16 => (633, "Level 50"),
+    635u16 => (634, "Level 64"),
+    637u16 => (636, "Level 59"),
+};
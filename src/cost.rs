@@ -0,0 +1,506 @@
+#![allow(unused, dead_code)]
+use crate::pokemon::{Pokemon, PokemonGender};
+use crate::tree::{PokemonBreedTree, PokemonBreedTreeNode, Position};
+use std::collections::HashMap;
+
+// Gender selection is priced by how likely the wanted gender is for the species:
+// common (50% or more), uncommon (25% or more) and rare (anything below).
+// Gender locked species don't need their gender picked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenderSelectionPrices {
+    pub common: u32,
+    pub uncommon: u32,
+    pub rare: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PokemonBreedCosts {
+    // 1x31 pokemon used as leaves of the tree
+    pub breeder: u32,
+    // 0x31 pokemon with the wanted nature
+    pub nature_breeder: u32,
+    // Dittos are priced by each 31 iv they carry, with a minimum of one
+    pub ditto: u32,
+    pub power_item: u32,
+    pub everstone: u32,
+    pub gender_selection: GenderSelectionPrices,
+    // Market prices of pokemon with more than one 31 iv, by number of ivs. Natured ones are
+    // priced apart. Pokemon without a price here can only be bred.
+    pub multi_iv_breeders: HashMap<usize, u32>,
+    pub multi_iv_nature_breeders: HashMap<usize, u32>,
+}
+
+impl Default for PokemonBreedCosts {
+    fn default() -> Self {
+        PokemonBreedCosts {
+            breeder: 10000,
+            nature_breeder: 5000,
+            ditto: 15000,
+            power_item: 10000,
+            everstone: 7000,
+            gender_selection: GenderSelectionPrices {
+                common: 5000,
+                uncommon: 9000,
+                rare: 21000,
+            },
+            multi_iv_breeders: HashMap::new(),
+            multi_iv_nature_breeders: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PokemonBreedTreeCost {
+    pub breeders: u32,
+    pub dittos: u32,
+    pub items: u32,
+    pub genders: u32,
+}
+
+impl PokemonBreedTreeCost {
+    pub fn total(&self) -> u32 {
+        self.breeders + self.dittos + self.items + self.genders
+    }
+}
+
+impl std::ops::AddAssign for PokemonBreedTreeCost {
+    fn add_assign(&mut self, other: PokemonBreedTreeCost) {
+        self.breeders += other.breeders;
+        self.dittos += other.dittos;
+        self.items += other.items;
+        self.genders += other.genders;
+    }
+}
+
+impl PokemonBreedCosts {
+    pub fn tree_cost(&self, tree: &PokemonBreedTree) -> PokemonBreedTreeCost {
+        self.nodes_cost(tree, |pos| !tree.owned.contains(&pos))
+    }
+
+    // Cost of the pokemon that still have to be acquired or bred
+    pub fn remaining_cost(&self, tree: &PokemonBreedTree) -> PokemonBreedTreeCost {
+        self.nodes_cost(tree, |pos| !tree.is_done(pos))
+    }
+
+    fn nodes_cost<F>(&self, tree: &PokemonBreedTree, include: F) -> PokemonBreedTreeCost
+    where
+        F: Fn(Position) -> bool,
+    {
+        let mut cost = PokemonBreedTreeCost::default();
+
+        for (pos, node) in &tree.pokemon_nodes {
+            if !include(*pos) {
+                continue;
+            }
+
+            let (p1_pos, p2_pos) = pos.get_parent_positions();
+            let parents = (
+                tree.pokemon_nodes.get(&p1_pos),
+                tree.pokemon_nodes.get(&p2_pos),
+            );
+
+            match parents {
+                (Some(p1_node), Some(p2_node)) => {
+                    cost.items += self.breed_items_price(node, p1_node, p2_node);
+                    cost.genders += self.gender_price(tree, *pos);
+                }
+                _ if node.is_ditto() => cost.dittos += self.ditto_price(node),
+                _ => cost.breeders += self.leaf_price(node),
+            }
+        }
+
+        cost
+    }
+
+    pub fn gender_selection_price(&self, chance: f32) -> u32 {
+        if chance >= 1.0 {
+            0
+        } else if chance >= 0.5 {
+            self.gender_selection.common
+        } else if chance >= 0.25 {
+            self.gender_selection.uncommon
+        } else {
+            self.gender_selection.rare
+        }
+    }
+
+    // Each parent holds an item: the one passing down the nature holds an everstone,
+    // the other one holds a power item
+    fn breed_items_price(
+        &self,
+        child: &PokemonBreedTreeNode,
+        p1: &PokemonBreedTreeNode,
+        p2: &PokemonBreedTreeNode,
+    ) -> u32 {
+        if child.nature.is_some() && (p1.nature == child.nature || p2.nature == child.nature) {
+            self.everstone + self.power_item
+        } else {
+            self.power_item * 2
+        }
+    }
+
+    // The final pokemon only pays for its gender when one was requested, every other bred
+    // pokemon needs its gender picked to form its pair
+    fn gender_price(&self, tree: &PokemonBreedTree, position: Position) -> u32 {
+        let node = tree.pokemon_nodes.get(&position).expect("Should exist");
+
+        match (&node.gender, tree.species_at(position)) {
+            (None, _) => 0,
+            // Dittos and genderless species have nothing to pick, a genderless pokemon of a
+            // gendered species is a breed error and is priced like the rarest gender
+            (Some(PokemonGender::Genderless), Some(pokemon)) => {
+                if pokemon.is_genderless() || pokemon.is_ditto() {
+                    0
+                } else {
+                    self.gender_selection.rare
+                }
+            }
+            (Some(PokemonGender::Genderless), None) => 0,
+            (Some(gender), Some(pokemon)) => {
+                self.gender_selection_price(pokemon.gender_chance(gender))
+            }
+            // The species of this node isn't decided yet, assume an even gender ratio
+            (Some(_), None) => self.gender_selection_price(0.5),
+        }
+    }
+
+    // Price of a 1x31 leaf, or of a 0x31 one with the wanted nature
+    pub fn breeder_price(&self, node: &PokemonBreedTreeNode) -> u32 {
+        if node.ivs.is_empty() && node.nature.is_some() {
+            self.nature_breeder
+        } else {
+            self.breeder
+        }
+    }
+
+    fn market_price(&self, node: &PokemonBreedTreeNode) -> Option<u32> {
+        if node.ivs.len() < 2 {
+            return None;
+        }
+
+        let prices = if node.nature.is_some() {
+            &self.multi_iv_nature_breeders
+        } else {
+            &self.multi_iv_breeders
+        };
+
+        prices.get(&node.ivs.len()).copied()
+    }
+
+    // Price of buying the pokemon instead of breeding it, None when it can't be bought
+    pub fn buy_price(&self, node: &PokemonBreedTreeNode) -> Option<u32> {
+        match node.ivs.len() {
+            0 | 1 => Some(self.breeder_price(node)),
+            _ => self.market_price(node),
+        }
+    }
+
+    // Price of a pokemon that isn't bred in the tree. A pokemon with several ivs that isn't on
+    // the market anymore, like one bought before prices changed, is priced like the 1x31
+    // pokemon it would take to breed it.
+    pub fn leaf_price(&self, node: &PokemonBreedTreeNode) -> u32 {
+        if node.is_ditto() {
+            return self.ditto_price(node);
+        }
+
+        self.buy_price(node)
+            .unwrap_or(self.breeder << node.ivs.len().saturating_sub(1))
+    }
+
+    // Goes through the tree from the final pokemon and buys every node that is cheaper to buy
+    // than to breed, with the cheapest way to get each of its parents taken into account.
+    // Returns the positions that are now bought instead of bred.
+    pub fn make_or_buy(&self, tree: &mut PokemonBreedTree) -> Vec<Position> {
+        let mut best_costs = HashMap::new();
+        self.best_cost(tree, Position::new(0, 0), &mut best_costs);
+
+        let mut bought = vec![];
+        let mut positions = vec![Position::new(0, 0)];
+        while let Some(pos) = positions.pop() {
+            if !tree.is_bred(pos) || tree.owned.contains(&pos) {
+                continue;
+            }
+
+            let (_, buy) = best_costs[&pos];
+            if buy {
+                tree.replace_with_purchase(pos, self)
+                    .expect("Should be for sale");
+                bought.push(pos);
+            } else {
+                let (p1_pos, p2_pos) = pos.get_parent_positions();
+                positions.push(p1_pos);
+                positions.push(p2_pos);
+            }
+        }
+        bought.sort();
+
+        bought
+    }
+
+    // The cheapest way to get the pokemon at this position, and whether that is buying it
+    fn best_cost(
+        &self,
+        tree: &PokemonBreedTree,
+        position: Position,
+        best_costs: &mut HashMap<Position, (u32, bool)>,
+    ) -> u32 {
+        let node = tree.pokemon_nodes.get(&position).expect("Should exist");
+        let (p1_pos, p2_pos) = position.get_parent_positions();
+
+        let best = if tree.owned.contains(&position) {
+            (0, false)
+        } else if !tree.is_bred(position) {
+            (self.leaf_price(node), false)
+        } else {
+            let p1_node = tree.pokemon_nodes.get(&p1_pos).expect("Should exist");
+            let p2_node = tree.pokemon_nodes.get(&p2_pos).expect("Should exist");
+            let breed_cost = self.breed_items_price(node, p1_node, p2_node)
+                + self.gender_price(tree, position)
+                + self.best_cost(tree, p1_pos, best_costs)
+                + self.best_cost(tree, p2_pos, best_costs);
+
+            match self.buy_price(node) {
+                Some(price) if price < breed_cost => (price, true),
+                _ => (breed_cost, false),
+            }
+        };
+        best_costs.insert(position, best);
+
+        best.0
+    }
+
+    pub fn ditto_price(&self, node: &PokemonBreedTreeNode) -> u32 {
+        self.ditto * node.ivs.len().max(1) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        fixtures::{charizard, final_node},
+        pokemon::{PokemonGender, PokemonIv, PokemonNature},
+        tree::{
+            PokemonBreedNodeState, PokemonBreedTree, PokemonBreedTreeError, PokemonBreederKind,
+            Position,
+        },
+    };
+
+    use super::PokemonBreedCosts;
+
+    #[test]
+    fn tree_cost_natured_2iv_test() {
+        let costs = PokemonBreedCosts::default();
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed],
+            Some(PokemonNature::Adamant),
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        let cost = costs.tree_cost(&tree);
+
+        // 3 breeds, two of them passing the nature. Charizard females are rare, the male
+        // species isn't decided yet so it's priced as an even gender ratio
+        assert_eq!(cost.breeders, costs.nature_breeder + costs.breeder * 3);
+        assert_eq!(cost.items, costs.power_item * 4 + costs.everstone * 2);
+        assert_eq!(
+            cost.genders,
+            costs.gender_selection.rare + costs.gender_selection.common
+        );
+        assert_eq!(cost.dittos, 0);
+    }
+
+    #[test]
+    fn tree_cost_ditto_test() {
+        let costs = PokemonBreedCosts::default();
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        tree.insert_ditto(
+            Position::new(1, 1),
+            vec![PokemonIv::Attack, PokemonIv::HP],
+            None,
+        )
+        .expect("Should be a valid ditto");
+        let cost = costs.tree_cost(&tree);
+
+        assert_eq!(cost.breeders, costs.breeder * 2);
+        assert_eq!(cost.dittos, costs.ditto * 2);
+        assert_eq!(cost.items, costs.power_item * 4);
+    }
+
+    #[test]
+    fn tree_cost_final_pokemon_gender_test() {
+        let costs = PokemonBreedCosts::default();
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let mut final_pokemon_node =
+            final_node(charizard(), &[PokemonIv::Attack, PokemonIv::Speed], None);
+        let any_gender_cost =
+            costs.tree_cost(&PokemonBreedTree::new(&final_pokemon_node, &final_iv_map));
+
+        final_pokemon_node.gender = Some(PokemonGender::Male);
+        let male_tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        assert_eq!(
+            male_tree.get_final_pokemon_node().gender,
+            Some(PokemonGender::Male)
+        );
+        assert_eq!(
+            costs.tree_cost(&male_tree).genders,
+            any_gender_cost.genders + costs.gender_selection.common
+        );
+
+        final_pokemon_node.gender = Some(PokemonGender::Female);
+        let female_tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        assert_eq!(
+            costs.tree_cost(&female_tree).genders,
+            any_gender_cost.genders + costs.gender_selection.rare
+        );
+    }
+
+    #[test]
+    fn tree_cost_genderless_final_pokemon_test() {
+        let costs = PokemonBreedCosts::default();
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let mut final_pokemon_node =
+            final_node(charizard(), &[PokemonIv::Attack, PokemonIv::Speed], None);
+        let any_gender_cost =
+            costs.tree_cost(&PokemonBreedTree::new(&final_pokemon_node, &final_iv_map));
+
+        // Charizard can't be genderless, it isn't free to ask for it
+        final_pokemon_node.gender = Some(PokemonGender::Genderless);
+        assert_eq!(
+            PokemonBreedTree::plan(&final_pokemon_node, &final_iv_map).err(),
+            Some(PokemonBreedTreeError::UnavailableGender(
+                Position::new(0, 0),
+                PokemonGender::Genderless
+            ))
+        );
+        let genderless_tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        assert_eq!(
+            costs.tree_cost(&genderless_tree).genders,
+            any_gender_cost.genders + costs.gender_selection.rare
+        );
+    }
+
+    #[test]
+    fn remaining_cost_test() {
+        let costs = PokemonBreedCosts::default();
+        let final_pokemon_node =
+            final_node(charizard(), &[PokemonIv::Attack, PokemonIv::Speed], None);
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        assert_eq!(costs.remaining_cost(&tree), costs.tree_cost(&tree));
+
+        tree.set_node_state(Position::new(1, 0), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        let remaining = costs.remaining_cost(&tree);
+
+        assert_eq!(remaining.breeders, costs.breeder);
+        assert_eq!(remaining.items, costs.power_item * 2);
+        assert_eq!(costs.tree_cost(&tree).breeders, costs.breeder * 2);
+    }
+
+    #[test]
+    fn remaining_cost_bred_subtree_test() {
+        let costs = PokemonBreedCosts::default();
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        assert_eq!(costs.tree_cost(&tree).breeders, costs.breeder * 4);
+
+        // The breeders of a bred pokemon were already bought
+        tree.set_node_state(Position::new(1, 0), PokemonBreedNodeState::Bred)
+            .expect("Should exist");
+        assert_eq!(costs.remaining_cost(&tree).breeders, costs.breeder * 2);
+    }
+
+    #[test]
+    fn leaf_price_test() {
+        let mut costs = PokemonBreedCosts::default();
+        costs.multi_iv_breeders.insert(1, 1000);
+        costs.multi_iv_breeders.insert(2, 30000);
+
+        // Market prices only apply to more than one 31 iv
+        let leaf = final_node(charizard(), &[PokemonIv::Attack], None);
+        assert_eq!(costs.buy_price(&leaf), Some(costs.breeder));
+        assert_eq!(costs.leaf_price(&leaf), costs.breeder);
+        let nature_leaf = final_node(charizard(), &[], Some(PokemonNature::Adamant));
+        assert_eq!(costs.leaf_price(&nature_leaf), costs.nature_breeder);
+
+        let bought = final_node(charizard(), &[PokemonIv::Attack, PokemonIv::Speed], None);
+        assert_eq!(costs.leaf_price(&bought), 30000);
+        let natured = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed],
+            Some(PokemonNature::Adamant),
+        );
+        assert_eq!(costs.buy_price(&natured), None);
+    }
+
+    #[test]
+    fn make_or_buy_test() {
+        let mut costs = PokemonBreedCosts::default();
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        // Nothing is on the market, so everything is bred
+        let mut bred_tree = tree.clone();
+        assert!(costs.make_or_buy(&mut bred_tree).is_empty());
+        assert_eq!(bred_tree.pokemon_nodes.len(), 7);
+
+        // Breeding a 2x31 takes two breeders, two power items and a gender, which is more than the
+        // market price only for the rare female
+        costs.multi_iv_breeders.insert(2, 50000);
+        let mut mixed_tree = tree.clone();
+        assert_eq!(
+            costs.make_or_buy(&mut mixed_tree),
+            vec![Position::new(1, 0)]
+        );
+        assert_eq!(mixed_tree.pokemon_nodes.len(), 5);
+        assert_eq!(
+            costs.tree_cost(&mixed_tree).total(),
+            costs.tree_cost(&tree).total() - costs.gender_selection.rare - costs.power_item * 2
+                + 50000
+                - costs.breeder * 2
+        );
+    }
+}
@@ -0,0 +1,179 @@
+#![allow(unused, dead_code)]
+use crate::cost::{PokemonBreedCosts, PokemonBreedTreeCost};
+use crate::pokemon::{same_ivs, Pokemon, PokemonGender, PokemonIv, PokemonNature};
+use crate::tree::{PokemonBreedTree, PokemonBreedTreeNode, Position};
+
+// A field of a node that is different between two trees, with its value before and after
+#[derive(Debug, Clone, PartialEq)]
+pub enum PokemonBreedFieldChange {
+    Species(Option<Pokemon>, Option<Pokemon>),
+    Gender(Option<PokemonGender>, Option<PokemonGender>),
+    Nature(Option<PokemonNature>, Option<PokemonNature>),
+    Ivs(Vec<PokemonIv>, Vec<PokemonIv>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PokemonBreedNodeChange {
+    pub position: Position,
+    pub changes: Vec<PokemonBreedFieldChange>,
+}
+
+// What changed from one tree to another. Positions are sorted by row and column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PokemonBreedTreeDiff {
+    pub added: Vec<Position>,
+    pub removed: Vec<Position>,
+    pub changed: Vec<PokemonBreedNodeChange>,
+    pub cost_before: PokemonBreedTreeCost,
+    pub cost_after: PokemonBreedTreeCost,
+}
+
+impl PokemonBreedTreeDiff {
+    pub fn new(
+        before: &PokemonBreedTree,
+        after: &PokemonBreedTree,
+        costs: &PokemonBreedCosts,
+    ) -> PokemonBreedTreeDiff {
+        let mut added = vec![];
+        let mut removed = vec![];
+        let mut changed = vec![];
+
+        for (pos, before_node) in &before.pokemon_nodes {
+            match after.pokemon_nodes.get(pos) {
+                None => removed.push(*pos),
+                Some(after_node) => {
+                    let changes = node_changes(before_node, after_node);
+                    if !changes.is_empty() {
+                        changed.push(PokemonBreedNodeChange {
+                            position: *pos,
+                            changes,
+                        });
+                    }
+                }
+            }
+        }
+        for pos in after.pokemon_nodes.keys() {
+            if !before.pokemon_nodes.contains_key(pos) {
+                added.push(*pos);
+            }
+        }
+
+        added.sort();
+        removed.sort();
+        changed.sort_by_key(|change| change.position);
+
+        PokemonBreedTreeDiff {
+            added,
+            removed,
+            changed,
+            cost_before: costs.tree_cost(before),
+            cost_after: costs.tree_cost(after),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    // How much more the second tree costs, negative when it's cheaper
+    pub fn cost_difference(&self) -> i64 {
+        self.cost_after.total() as i64 - self.cost_before.total() as i64
+    }
+}
+
+pub fn node_changes(
+    before: &PokemonBreedTreeNode,
+    after: &PokemonBreedTreeNode,
+) -> Vec<PokemonBreedFieldChange> {
+    let mut changes = vec![];
+
+    if before.pokemon != after.pokemon {
+        changes.push(PokemonBreedFieldChange::Species(
+            before.pokemon.clone(),
+            after.pokemon.clone(),
+        ));
+    }
+    if before.gender != after.gender {
+        changes.push(PokemonBreedFieldChange::Gender(
+            before.gender.clone(),
+            after.gender.clone(),
+        ));
+    }
+    if before.nature != after.nature {
+        changes.push(PokemonBreedFieldChange::Nature(before.nature, after.nature));
+    }
+    if !same_ivs(&before.ivs, &after.ivs) {
+        changes.push(PokemonBreedFieldChange::Ivs(
+            before.ivs.clone(),
+            after.ivs.clone(),
+        ));
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        cost::PokemonBreedCosts,
+        fixtures::{charmander, final_node},
+        pokemon::{PokemonGender, PokemonIv},
+        tree::{PokemonBreedTree, PokemonBreedTreeNode, PokemonBreederKind, Position},
+    };
+
+    use super::{PokemonBreedFieldChange, PokemonBreedNodeChange, PokemonBreedTreeDiff};
+
+    #[test]
+    fn tree_diff_test() {
+        let mut costs = PokemonBreedCosts::default();
+        costs.multi_iv_breeders.insert(2, 30000);
+        let charmander = charmander();
+        let final_pokemon_node = final_node(
+            charmander.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let before = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        assert!(PokemonBreedTreeDiff::new(&before, &before, &costs).is_empty());
+
+        let mut after = before.clone();
+        after
+            .replace_with_purchase(Position::new(1, 1), &costs)
+            .expect("Should be for sale");
+        after.pokemon_nodes.insert(
+            Position::new(2, 1),
+            PokemonBreedTreeNode {
+                pokemon: Some(charmander.clone()),
+                gender: Some(PokemonGender::Male),
+                nature: None,
+                ivs: vec![PokemonIv::Speed],
+                evolution_note: None,
+            },
+        );
+        let diff = PokemonBreedTreeDiff::new(&before, &after, &costs);
+
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec![Position::new(2, 2), Position::new(2, 3)]);
+        assert_eq!(
+            diff.changed,
+            vec![PokemonBreedNodeChange {
+                position: Position::new(2, 1),
+                changes: vec![PokemonBreedFieldChange::Species(None, Some(charmander))],
+            }]
+        );
+        // The bought 2x31 is paid its market price instead of its two breeders and its breed
+        let breed_cost = costs.breeder * 2 + costs.power_item * 2 + costs.gender_selection.common;
+        assert_eq!(diff.cost_difference(), 30000 - breed_cost as i64);
+
+        let reverse = PokemonBreedTreeDiff::new(&after, &before, &costs);
+        assert_eq!(reverse.added, diff.removed);
+        assert_eq!(reverse.cost_difference(), -diff.cost_difference());
+    }
+}
@@ -0,0 +1,74 @@
+#![allow(unused, dead_code)]
+use crate::breeder::Breeder;
+use crate::cost::PokemonBreedCosts;
+use crate::inventory::InventoryPokemon;
+use crate::pokedex::Pokedex;
+use crate::pokemon::{
+    Pokemon, PokemonEggGroup, PokemonGender, PokemonIv, PokemonNature, PokemonType,
+};
+use crate::target::PokemonBreedTarget;
+use crate::tree::PokemonBreedTreeNode;
+
+// Pokemon and setups shared by the tests of the other modules
+
+pub fn charmander() -> Pokemon {
+    Pokemon {
+        number: 4,
+        name: "Charmander".to_string(),
+        types: (PokemonType::Fire, None),
+        egg_groups: (PokemonEggGroup::Monster, Some(PokemonEggGroup::Dragon)),
+        percentage_male: 87.5,
+    }
+}
+
+pub fn charizard() -> Pokemon {
+    Pokemon {
+        number: 6,
+        name: "Charizard".to_string(),
+        types: (PokemonType::Fire, Some(PokemonType::Flying)),
+        ..charmander()
+    }
+}
+
+// The node a tree is built from, with no gender or evolution note decided
+pub fn final_node(
+    pokemon: Pokemon,
+    ivs: &[PokemonIv],
+    nature: Option<PokemonNature>,
+) -> PokemonBreedTreeNode {
+    PokemonBreedTreeNode {
+        ivs: ivs.to_vec(),
+        pokemon: Some(pokemon),
+        gender: None,
+        nature,
+        evolution_note: None,
+    }
+}
+
+// A female Charmander with the given ivs, as kept in the inventory
+pub fn owned_charmander(ivs: &[PokemonIv]) -> InventoryPokemon {
+    InventoryPokemon {
+        pokemon: charmander(),
+        gender: PokemonGender::Female,
+        nature: PokemonNature::Bold,
+        ivs: ivs.to_vec(),
+        held_item: None,
+        tag: None,
+    }
+}
+
+// A session with only Charmander in the pokedex and a natureless Charmander as its target
+pub fn charmander_breeder(ivs: &[PokemonIv]) -> Breeder {
+    let mut breeder = Breeder::new(
+        Pokedex::new(vec![charmander()]),
+        PokemonBreedCosts::default(),
+    );
+    breeder.set_target(
+        PokemonBreedTarget::builder(&breeder.pokedex)
+            .species_number(4)
+            .ivs(ivs)
+            .build()
+            .expect("Should be a valid target"),
+    );
+    breeder
+}
@@ -0,0 +1,189 @@
+#![allow(unused, dead_code)]
+use std::collections::HashMap;
+
+// A change that can be undone: what it was, and the state from before it
+#[derive(Debug, Clone)]
+struct HistoryEntry<T> {
+    description: String,
+    snapshot: T,
+}
+
+// Edit history kept as snapshots of the whole state, so any change can be undone without each
+// edit knowing how to revert itself. Checkpoints are named snapshots that can be gone back to
+// at any time, going back to one can be undone like any other change.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<HistoryEntry<T>>,
+    redo: Vec<HistoryEntry<T>>,
+    checkpoints: HashMap<String, T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        History {
+            undo: vec![],
+            redo: vec![],
+            checkpoints: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Clone> History<T> {
+    pub fn new() -> History<T> {
+        History::default()
+    }
+
+    // Stores the state from before a change. Anything that was undone can't be redone anymore.
+    pub fn record(&mut self, description: &str, before: &T) {
+        self.undo.push(HistoryEntry {
+            description: description.to_string(),
+            snapshot: before.clone(),
+        });
+        self.redo.clear();
+    }
+
+    // Puts back the state from before the last change, and returns what the change was
+    pub fn undo(&mut self, current: &mut T) -> Option<String> {
+        let entry = self.undo.pop()?;
+        let after = std::mem::replace(current, entry.snapshot);
+        self.redo.push(HistoryEntry {
+            description: entry.description.clone(),
+            snapshot: after,
+        });
+
+        Some(entry.description)
+    }
+
+    pub fn redo(&mut self, current: &mut T) -> Option<String> {
+        let entry = self.redo.pop()?;
+        let before = std::mem::replace(current, entry.snapshot);
+        self.undo.push(HistoryEntry {
+            description: entry.description.clone(),
+            snapshot: before,
+        });
+
+        Some(entry.description)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // What each change that can be undone was, from the oldest to the last one
+    pub fn undo_descriptions(&self) -> Vec<&str> {
+        self.undo
+            .iter()
+            .map(|entry| entry.description.as_str())
+            .collect()
+    }
+
+    pub fn redo_descriptions(&self) -> Vec<&str> {
+        self.redo
+            .iter()
+            .rev()
+            .map(|entry| entry.description.as_str())
+            .collect()
+    }
+
+    // Saves the current state under this name, replacing any checkpoint with the same name
+    pub fn checkpoint(&mut self, name: &str, current: &T) {
+        self.checkpoints.insert(name.to_string(), current.clone());
+    }
+
+    pub fn checkpoint_names(&self) -> Vec<&str> {
+        let mut names = self
+            .checkpoints
+            .keys()
+            .map(|name| name.as_str())
+            .collect::<Vec<&str>>();
+        names.sort();
+
+        names
+    }
+
+    // Goes back to the checkpoint, returns false when there is no checkpoint with this name
+    pub fn restore(&mut self, name: &str, current: &mut T) -> bool {
+        let Some(snapshot) = self.checkpoints.get(name).cloned() else {
+            return false;
+        };
+
+        self.record(&format!("Restore {}", name), current);
+        *current = snapshot;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        fixtures::{charizard, final_node},
+        pokemon::PokemonIv,
+        tree::{PokemonBreedTree, PokemonBreederKind, Position},
+    };
+
+    use super::History;
+
+    #[test]
+    fn history_test() {
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        let mut history = History::new();
+        let ivs = |tree: &PokemonBreedTree| tree.pokemon_nodes[&Position::new(1, 1)].ivs.clone();
+        let original_ivs = ivs(&tree);
+
+        history.checkpoint("generated", &tree);
+        history.record("Swap parents", &tree);
+        tree.swap_parents(Position::new(1, 1))
+            .expect("Should be a pair");
+        history.record("Change breeder kind", &tree);
+        tree.set_breeder_kind(Position::new(2, 3), PokemonBreederKind::B)
+            .expect("Should be a leaf");
+        let edited_ivs = ivs(&tree);
+        assert_eq!(
+            history.undo_descriptions(),
+            vec!["Swap parents", "Change breeder kind"]
+        );
+
+        assert_eq!(
+            history.undo(&mut tree).as_deref(),
+            Some("Change breeder kind")
+        );
+        assert_eq!(history.undo(&mut tree).as_deref(), Some("Swap parents"));
+        assert_eq!(history.undo(&mut tree), None);
+        assert_eq!(ivs(&tree), original_ivs);
+
+        assert_eq!(history.redo(&mut tree).as_deref(), Some("Swap parents"));
+        assert_eq!(
+            history.redo(&mut tree).as_deref(),
+            Some("Change breeder kind")
+        );
+        assert!(!history.can_redo());
+        assert_eq!(ivs(&tree), edited_ivs);
+
+        // Going back to a checkpoint is a change of its own
+        assert!(history.restore("generated", &mut tree));
+        assert_eq!(ivs(&tree), original_ivs);
+        assert_eq!(
+            history.undo(&mut tree).as_deref(),
+            Some("Restore generated")
+        );
+        assert_eq!(ivs(&tree), edited_ivs);
+        assert!(!history.restore("missing", &mut tree));
+    }
+}
@@ -0,0 +1,312 @@
+#![allow(unused, dead_code)]
+use crate::cost::PokemonBreedCosts;
+use crate::pokemon::{Pokemon, PokemonGender, PokemonHeldItem, PokemonIv, PokemonNature};
+use crate::tree::{PokemonBreedTree, PokemonBreedTreeNode, Position};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub type InventoryId = u32;
+
+// A pokemon the user already owns. Ivs are the stats that are 31, like in the breed tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryPokemon {
+    pub pokemon: Pokemon,
+    pub gender: PokemonGender,
+    pub nature: PokemonNature,
+    pub ivs: Vec<PokemonIv>,
+    pub held_item: Option<PokemonHeldItem>,
+    // Free text to find the pokemon again, like a box name or a note
+    pub tag: Option<String>,
+}
+
+impl InventoryPokemon {
+    pub fn has_ivs(&self, ivs: &[PokemonIv]) -> bool {
+        ivs.iter().all(|iv| self.ivs.contains(iv))
+    }
+
+    // The pokemon as the node it takes the place of. Its nature only matters where the plan
+    // needs one, anywhere else it would be passed down to the final pokemon.
+    pub fn to_node(&self, planned: &PokemonBreedTreeNode) -> PokemonBreedTreeNode {
+        PokemonBreedTreeNode {
+            pokemon: Some(self.pokemon.clone()),
+            gender: Some(self.gender.clone()),
+            nature: planned.nature.and(Some(self.nature)),
+            ivs: self.ivs.clone(),
+            evolution_note: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InventorySlotReport {
+    pub slotted: Vec<(Position, InventoryId)>,
+    pub breeds_saved: usize,
+    pub money_saved: u32,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Inventory {
+    pokemon: BTreeMap<InventoryId, InventoryPokemon>,
+    next_id: InventoryId,
+    // Pokemon set aside for a plan, they stay owned but can't be used anywhere else
+    reserved: BTreeSet<InventoryId>,
+}
+
+impl Inventory {
+    pub fn new() -> Inventory {
+        Inventory::default()
+    }
+
+    pub fn add(&mut self, pokemon: InventoryPokemon) -> InventoryId {
+        let id = self.next_id;
+        self.pokemon.insert(id, pokemon);
+        self.next_id += 1;
+
+        id
+    }
+
+    pub fn remove(&mut self, id: InventoryId) -> Option<InventoryPokemon> {
+        self.reserved.remove(&id);
+        self.pokemon.remove(&id)
+    }
+
+    // Returns false when there is no such pokemon, or it's already reserved
+    pub fn reserve(&mut self, id: InventoryId) -> bool {
+        self.pokemon.contains_key(&id) && self.reserved.insert(id)
+    }
+
+    pub fn release(&mut self, id: InventoryId) -> bool {
+        self.reserved.remove(&id)
+    }
+
+    pub fn is_reserved(&self, id: InventoryId) -> bool {
+        self.reserved.contains(&id)
+    }
+
+    pub fn get(&self, id: InventoryId) -> Option<&InventoryPokemon> {
+        self.pokemon.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pokemon.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pokemon.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (InventoryId, &InventoryPokemon)> {
+        self.pokemon.iter().map(|(id, pokemon)| (*id, pokemon))
+    }
+
+    pub fn query<F>(&self, filter: F) -> Vec<(InventoryId, &InventoryPokemon)>
+    where
+        F: Fn(&InventoryPokemon) -> bool,
+    {
+        self.iter().filter(|(_, pokemon)| filter(pokemon)).collect()
+    }
+
+    pub fn with_tag(&self, tag: &str) -> Vec<(InventoryId, &InventoryPokemon)> {
+        self.query(|pokemon| pokemon.tag.as_deref() == Some(tag))
+    }
+
+    // Pokemon that aren't reserved by a plan
+    pub fn available(&self) -> impl Iterator<Item = (InventoryId, &InventoryPokemon)> {
+        self.iter().filter(|(id, _)| !self.is_reserved(*id))
+    }
+
+    // Removes and returns the first available pokemon matching the filter, for when the planner
+    // uses it up
+    pub fn take<F>(&mut self, filter: F) -> Option<(InventoryId, InventoryPokemon)>
+    where
+        F: Fn(&InventoryPokemon) -> bool,
+    {
+        let id = self
+            .available()
+            .find(|(_, pokemon)| filter(pokemon))
+            .map(|(id, _)| id)?;
+
+        self.remove(id).map(|pokemon| (id, pokemon))
+    }
+
+    // Fills the tree with owned pokemon, starting from the positions closest to the final pokemon
+    // since they replace the biggest subtrees. Each node gets the matching pokemon with the fewest
+    // ivs, so the better ones are kept for where they're needed. Slotted pokemon are reserved for
+    // the tree, and pokemon already reserved are left alone.
+    pub fn slot_into(
+        &mut self,
+        tree: &mut PokemonBreedTree,
+        costs: &PokemonBreedCosts,
+    ) -> InventorySlotReport {
+        let cost_before = costs.tree_cost(tree).total();
+        let breeds_before = tree.breed_count();
+
+        let mut positions = tree
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| !pos.is_root() && !tree.owned.contains(pos))
+            .copied()
+            .collect::<Vec<Position>>();
+        positions.sort();
+
+        let mut slotted = vec![];
+        for pos in positions {
+            // Already removed by a pokemon slotted below it
+            if !tree.pokemon_nodes.contains_key(&pos) {
+                continue;
+            }
+
+            let planned = tree.pokemon_nodes[&pos].clone();
+            let best_match = self
+                .available()
+                .filter(|(_, pokemon)| tree.accepts_pokemon(pos, &pokemon.to_node(&planned)))
+                .min_by_key(|(_, pokemon)| pokemon.ivs.len())
+                .map(|(id, _)| id);
+
+            if let Some(id) = best_match {
+                self.reserve(id);
+                let node = self.pokemon[&id].to_node(&planned);
+                tree.insert_owned_pokemon(pos, node);
+                slotted.push((pos, id));
+            }
+        }
+
+        InventorySlotReport {
+            slotted,
+            // An owned pokemon can make its partner a pricier gender, so the tree can cost more
+            breeds_saved: breeds_before.saturating_sub(tree.breed_count()),
+            money_saved: cost_before.saturating_sub(costs.tree_cost(tree).total()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        cost::PokemonBreedCosts,
+        fixtures::{charizard, charmander, final_node, owned_charmander},
+        pokemon::{PokemonGender, PokemonHeldItem, PokemonIv, PokemonNature},
+        tree::{PokemonBreedTree, PokemonBreederKind, Position},
+    };
+
+    use super::{Inventory, InventoryPokemon};
+
+    #[test]
+    fn inventory_test() {
+        let charmander = InventoryPokemon {
+            nature: PokemonNature::Adamant,
+            held_item: Some(PokemonHeldItem::PowerBracer),
+            tag: Some("Box 1".to_string()),
+            ..owned_charmander(&[PokemonIv::Attack, PokemonIv::Speed])
+        };
+        let mut inventory = Inventory::new();
+        let female_id = inventory.add(charmander.clone());
+        let male_id = inventory.add(InventoryPokemon {
+            gender: PokemonGender::Male,
+            ivs: vec![PokemonIv::Speed],
+            tag: None,
+            ..charmander.clone()
+        });
+
+        assert_eq!(inventory.len(), 2);
+        assert_eq!(inventory.with_tag("Box 1").len(), 1);
+        assert_eq!(
+            inventory
+                .query(|pokemon| pokemon.has_ivs(&[PokemonIv::Speed]))
+                .len(),
+            2
+        );
+
+        let taken = inventory.take(|pokemon| pokemon.gender == PokemonGender::Male);
+        assert_eq!(taken.map(|(id, _)| id), Some(male_id));
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(inventory.remove(female_id), Some(charmander));
+        assert!(inventory.is_empty());
+    }
+
+    #[test]
+    fn inventory_slot_into_test() {
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        let female_attack = InventoryPokemon {
+            nature: PokemonNature::Hardy,
+            ..owned_charmander(&[PokemonIv::Attack])
+        };
+        let mut inventory = Inventory::new();
+        let female_id = inventory.add(female_attack.clone());
+        let male_id = inventory.add(InventoryPokemon {
+            gender: PokemonGender::Male,
+            ivs: vec![PokemonIv::Attack, PokemonIv::HP, PokemonIv::Speed],
+            ..female_attack.clone()
+        });
+        // A male can't take the place of the female line
+        inventory.add(InventoryPokemon {
+            gender: PokemonGender::Male,
+            ..female_attack.clone()
+        });
+
+        let costs = PokemonBreedCosts::default();
+        let report = inventory.slot_into(&mut tree, &costs);
+
+        assert_eq!(
+            report.slotted,
+            vec![
+                (Position::new(1, 1), male_id),
+                (Position::new(2, 0), female_id)
+            ]
+        );
+        assert_eq!(report.breeds_saved, 1);
+        // Two breeders and the breed for the male, plus the female breeder
+        assert_eq!(
+            report.money_saved,
+            costs.breeder * 3 + costs.power_item * 2 + costs.gender_selection.common
+        );
+        assert_eq!(tree.pokemon_nodes.len(), 5);
+        // Slotted pokemon are only reserved, they're still owned
+        assert_eq!(inventory.len(), 3);
+        assert!(inventory.is_reserved(male_id) && inventory.is_reserved(female_id));
+        assert_eq!(inventory.available().count(), 1);
+    }
+
+    #[test]
+    fn inventory_slot_into_natureless_test() {
+        let final_pokemon_node = final_node(
+            charmander(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        let mut inventory = Inventory::new();
+        let id = inventory.add(InventoryPokemon {
+            gender: PokemonGender::Male,
+            nature: PokemonNature::Adamant,
+            ..owned_charmander(&tree.pokemon_nodes[&Position::new(2, 3)].ivs)
+        });
+        let report = inventory.slot_into(&mut tree, &PokemonBreedCosts::default());
+        assert_eq!(report.slotted, vec![(Position::new(2, 3), id)]);
+
+        // The nature of the owned pokemon isn't part of the plan, so it doesn't reach the final
+        // pokemon when the plan is edited
+        assert_eq!(tree.pokemon_nodes[&Position::new(2, 3)].nature, None);
+        tree.swap_parents(Position::new(1, 1))
+            .expect("Should keep the final pokemon");
+        assert_eq!(tree.get_final_pokemon_node().nature, None);
+    }
+}
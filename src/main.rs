@@ -1,6 +1,17 @@
 mod breeder;
 mod consts;
+mod cost;
+mod diff;
+#[cfg(test)]
+mod fixtures;
+mod history;
+mod inventory;
+mod merge;
+mod planner;
+mod pokedex;
 mod pokemon;
+mod shopping;
+mod target;
 mod tree;
 
 fn main() {
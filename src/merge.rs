@@ -0,0 +1,453 @@
+#![allow(unused, dead_code)]
+use std::collections::HashSet;
+use std::mem::discriminant;
+
+use crate::diff::{node_changes, PokemonBreedFieldChange};
+use crate::tree::{PokemonBreedNodeState, PokemonBreedTree, PokemonBreedTreeNode, Position};
+
+// Edits of the same node that can't both be kept. The merged tree keeps ours for each of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PokemonBreedMergeConflict {
+    // Both sides changed the same field of a node to different values
+    Field {
+        position: Position,
+        ours: PokemonBreedFieldChange,
+        theirs: PokemonBreedFieldChange,
+    },
+    // One side removed or added the node while the other one changed it
+    Node {
+        position: Position,
+        ours: Option<PokemonBreedTreeNode>,
+        theirs: Option<PokemonBreedTreeNode>,
+    },
+    Progress {
+        position: Position,
+        ours: PokemonBreedNodeState,
+        theirs: PokemonBreedNodeState,
+    },
+    // One side changed which pokemon are bred to obtain the one at this position, like buying
+    // it instead, while the other one edited some of them. The subtree is kept whole.
+    Subtree {
+        position: Position,
+    },
+}
+
+impl PokemonBreedMergeConflict {
+    pub fn position(&self) -> Position {
+        match self {
+            PokemonBreedMergeConflict::Field { position, .. }
+            | PokemonBreedMergeConflict::Node { position, .. }
+            | PokemonBreedMergeConflict::Progress { position, .. }
+            | PokemonBreedMergeConflict::Subtree { position } => *position,
+        }
+    }
+}
+
+// Two copies of the same plan edited separately, merged back into one. Changes made on only
+// one side are applied, changes made on both sides are kept when they agree and reported as
+// conflicts otherwise.
+#[derive(Debug, Clone)]
+pub struct PokemonBreedTreeMerge {
+    pub tree: PokemonBreedTree,
+    pub conflicts: Vec<PokemonBreedMergeConflict>,
+}
+
+impl PokemonBreedTreeMerge {
+    pub fn new(
+        base: &PokemonBreedTree,
+        ours: &PokemonBreedTree,
+        theirs: &PokemonBreedTree,
+    ) -> PokemonBreedTreeMerge {
+        let mut tree = ours.clone();
+        let mut conflicts = vec![];
+
+        let mut positions = base
+            .pokemon_nodes
+            .keys()
+            .chain(ours.pokemon_nodes.keys())
+            .chain(theirs.pokemon_nodes.keys())
+            .copied()
+            .collect::<HashSet<Position>>()
+            .into_iter()
+            .collect::<Vec<Position>>();
+        positions.sort();
+
+        // Subtrees where a side added or removed nodes are merged as a whole, never node by node,
+        // so no node is left without the child it's bred for
+        let mut merged_subtrees = HashSet::new();
+        let our_roots = structural_roots(base, ours);
+        let their_roots = structural_roots(base, theirs);
+        let mut roots = our_roots
+            .iter()
+            .chain(&their_roots)
+            .copied()
+            .collect::<Vec<_>>();
+        roots.sort();
+        roots.dedup();
+        for root in roots {
+            if roots_contain(&our_roots, &their_roots, root) {
+                continue;
+            }
+
+            let subtree = positions
+                .iter()
+                .filter(|pos| pos.is_in_subtree_of(root) && **pos != root)
+                .copied()
+                .collect::<Vec<Position>>();
+            let ours_changed = subtree.iter().any(|pos| node_edited(base, ours, *pos));
+            let theirs_changed = subtree.iter().any(|pos| node_edited(base, theirs, *pos));
+            let same_edit = subtree.iter().all(|pos| !node_edited(ours, theirs, *pos));
+
+            if ours_changed && theirs_changed && !same_edit {
+                conflicts.push(PokemonBreedMergeConflict::Subtree { position: root });
+            } else if theirs_changed && !ours_changed {
+                for pos in &subtree {
+                    take_node(&mut tree, theirs, *pos);
+                }
+            }
+            merged_subtrees.extend(subtree);
+        }
+
+        for pos in positions
+            .iter()
+            .filter(|pos| !merged_subtrees.contains(pos))
+        {
+            let base_node = base.pokemon_nodes.get(pos);
+            let our_node = ours.pokemon_nodes.get(pos);
+            let their_node = theirs.pokemon_nodes.get(pos);
+
+            if their_node == base_node || our_node == their_node {
+                continue;
+            }
+            match (our_node, their_node) {
+                _ if our_node == base_node => match their_node {
+                    Some(node) => {
+                        tree.pokemon_nodes.insert(*pos, node.clone());
+                    }
+                    None => {
+                        tree.pokemon_nodes.remove(pos);
+                    }
+                },
+                (Some(our_node), Some(their_node)) => {
+                    let empty_node = PokemonBreedTreeNode {
+                        pokemon: None,
+                        gender: None,
+                        nature: None,
+                        ivs: vec![],
+                        evolution_note: None,
+                    };
+                    let base_node = base_node.unwrap_or(&empty_node);
+                    let node = tree.pokemon_nodes.get_mut(pos).expect("Should exist");
+                    conflicts.extend(merge_node(*pos, base_node, our_node, their_node, node));
+                }
+                _ => conflicts.push(PokemonBreedMergeConflict::Node {
+                    position: *pos,
+                    ours: our_node.cloned(),
+                    theirs: their_node.cloned(),
+                }),
+            }
+        }
+
+        for pos in positions
+            .iter()
+            .filter(|pos| !merged_subtrees.contains(pos))
+        {
+            let base_state = base.node_state(*pos);
+            let our_state = ours.node_state(*pos);
+            let their_state = theirs.node_state(*pos);
+            if their_state == base_state || our_state == their_state {
+                continue;
+            }
+
+            if our_state == base_state {
+                tree.progress.insert(*pos, their_state);
+            } else {
+                conflicts.push(PokemonBreedMergeConflict::Progress {
+                    position: *pos,
+                    ours: our_state,
+                    theirs: their_state,
+                });
+            }
+        }
+
+        // Whether a pokemon is owned can't conflict, when both sides changed it they agree
+        for pos in positions
+            .iter()
+            .filter(|pos| !merged_subtrees.contains(pos))
+        {
+            let base_owned = base.owned.contains(pos);
+            if theirs.owned.contains(pos) != base_owned {
+                if base_owned {
+                    tree.owned.remove(pos);
+                } else {
+                    tree.owned.insert(*pos);
+                }
+            }
+        }
+
+        if ours.ivs_map == base.ivs_map {
+            tree.ivs_map = theirs.ivs_map.clone();
+        }
+
+        let pokemon_nodes = &tree.pokemon_nodes;
+        tree.owned.retain(|pos| pokemon_nodes.contains_key(pos));
+        tree.progress
+            .retain(|pos, _| pokemon_nodes.contains_key(pos));
+        // Subtrees taken from one side can have genders picked for a partner from the other
+        // side, so every pair gets its genders again, which validates the tree too
+        tree.assign_genders();
+        // The merged tree is new, what ours was changed by isn't a change of it
+        tree.take_events();
+
+        PokemonBreedTreeMerge { tree, conflicts }
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+// The pokemon whose parents were added or removed on the changed side, leaving out the ones
+// inside a subtree that already changed
+fn structural_roots(base: &PokemonBreedTree, changed: &PokemonBreedTree) -> Vec<Position> {
+    let mut roots = base
+        .pokemon_nodes
+        .keys()
+        .chain(changed.pokemon_nodes.keys())
+        .filter(|pos| {
+            base.pokemon_nodes.contains_key(pos) != changed.pokemon_nodes.contains_key(pos)
+        })
+        .filter_map(|pos| {
+            let mut root = pos.get_child_position()?;
+            while !(base.pokemon_nodes.contains_key(&root)
+                && changed.pokemon_nodes.contains_key(&root))
+            {
+                root = root.get_child_position()?;
+            }
+            Some(root)
+        })
+        .collect::<Vec<Position>>();
+    roots.sort();
+    roots.dedup();
+
+    let nested = roots.clone();
+    roots.retain(|root| {
+        !nested
+            .iter()
+            .any(|other| other != root && root.is_in_subtree_of(*other))
+    });
+    roots
+}
+
+// Whether the subtree of the root is inside the subtree of another root of either side, which
+// is merged as a whole already
+fn roots_contain(our_roots: &[Position], their_roots: &[Position], root: Position) -> bool {
+    our_roots
+        .iter()
+        .chain(their_roots)
+        .any(|other| *other != root && root.is_in_subtree_of(*other))
+}
+
+// Whether the node, its progress or whether it's owned is different between the two trees
+fn node_edited(before: &PokemonBreedTree, after: &PokemonBreedTree, position: Position) -> bool {
+    before.pokemon_nodes.get(&position) != after.pokemon_nodes.get(&position)
+        || before.node_state(position) != after.node_state(position)
+        || before.owned.contains(&position) != after.owned.contains(&position)
+}
+
+// Puts the node at this position as it is in the other tree, along with its progress and
+// whether it's owned
+fn take_node(tree: &mut PokemonBreedTree, other: &PokemonBreedTree, position: Position) {
+    match other.pokemon_nodes.get(&position) {
+        Some(node) => {
+            tree.pokemon_nodes.insert(position, node.clone());
+        }
+        None => {
+            tree.pokemon_nodes.remove(&position);
+        }
+    }
+    match other.progress.get(&position) {
+        Some(state) => {
+            tree.progress.insert(position, *state);
+        }
+        None => {
+            tree.progress.remove(&position);
+        }
+    }
+    if other.owned.contains(&position) {
+        tree.owned.insert(position);
+    } else {
+        tree.owned.remove(&position);
+    }
+}
+
+// Applies the fields only they changed to the node, which starts as ours
+fn merge_node(
+    position: Position,
+    base: &PokemonBreedTreeNode,
+    ours: &PokemonBreedTreeNode,
+    theirs: &PokemonBreedTreeNode,
+    node: &mut PokemonBreedTreeNode,
+) -> Vec<PokemonBreedMergeConflict> {
+    let our_changes = node_changes(base, ours);
+    let disagreements = node_changes(ours, theirs);
+    let mut conflicts = vec![];
+
+    for their_change in node_changes(base, theirs) {
+        let same_field =
+            |change: &PokemonBreedFieldChange| discriminant(change) == discriminant(&their_change);
+        let Some(our_change) = our_changes.iter().find(|change| same_field(change)) else {
+            apply_change(node, their_change);
+            continue;
+        };
+
+        if disagreements.iter().any(same_field) {
+            conflicts.push(PokemonBreedMergeConflict::Field {
+                position,
+                ours: our_change.clone(),
+                theirs: their_change,
+            });
+        }
+    }
+
+    conflicts
+}
+
+fn apply_change(node: &mut PokemonBreedTreeNode, change: PokemonBreedFieldChange) {
+    match change {
+        PokemonBreedFieldChange::Species(_, pokemon) => node.pokemon = pokemon,
+        PokemonBreedFieldChange::Gender(_, gender) => node.gender = gender,
+        PokemonBreedFieldChange::Nature(_, nature) => node.nature = nature,
+        PokemonBreedFieldChange::Ivs(_, ivs) => node.ivs = ivs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        cost::PokemonBreedCosts,
+        diff::PokemonBreedFieldChange,
+        fixtures::{charmander, final_node},
+        pokemon::{Pokemon, PokemonEggGroup, PokemonGender, PokemonIv, PokemonType},
+        tree::{PokemonBreedNodeState, PokemonBreedTree, PokemonBreederKind, Position},
+    };
+
+    use super::{PokemonBreedMergeConflict, PokemonBreedTreeMerge};
+
+    #[test]
+    fn tree_merge_test() {
+        let mut costs = PokemonBreedCosts::default();
+        costs.multi_iv_breeders.insert(2, 30000);
+        let charmander = charmander();
+        let bagon = Pokemon {
+            number: 371,
+            name: "Bagon".to_string(),
+            types: (PokemonType::Dragon, None),
+            egg_groups: (PokemonEggGroup::Dragon, None),
+            percentage_male: 50.0,
+        };
+        let final_pokemon_node = final_node(
+            charmander.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let base = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        let species =
+            |tree: &PokemonBreedTree, pos: Position| tree.pokemon_nodes[&pos].pokemon.clone();
+
+        // We assign species, they mark progress and buy a pokemon
+        let mut ours = base.clone();
+        ours.pokemon_nodes
+            .get_mut(&Position::new(2, 1))
+            .expect("Should exist")
+            .pokemon = Some(bagon.clone());
+        ours.pokemon_nodes
+            .get_mut(&Position::new(2, 3))
+            .expect("Should exist")
+            .pokemon = Some(charmander.clone());
+        let mut theirs = base.clone();
+        theirs
+            .set_node_state(Position::new(2, 0), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        theirs
+            .replace_with_purchase(Position::new(1, 1), &costs)
+            .expect("Should be for sale");
+        theirs
+            .pokemon_nodes
+            .get_mut(&Position::new(2, 1))
+            .expect("Should exist")
+            .gender = Some(PokemonGender::Female);
+
+        ours.set_node_state(Position::new(2, 2), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        let mut merge = PokemonBreedTreeMerge::new(&base, &ours, &theirs);
+        assert_eq!(
+            species(&merge.tree, Position::new(2, 1)),
+            Some(bagon.clone())
+        );
+        // Genders are assigned again after the merge, a male column stays male
+        assert_eq!(
+            merge.tree.pokemon_nodes[&Position::new(2, 1)].gender,
+            Some(PokemonGender::Male)
+        );
+        // Our progress was merged, it isn't an event of the merged tree
+        assert!(merge.tree.take_events().is_empty());
+        assert_eq!(
+            merge.tree.node_state(Position::new(2, 0)),
+            PokemonBreedNodeState::Acquired
+        );
+        // They removed the parents of (1, 1) when buying it, and we changed one of them, so
+        // both parents are kept as we have them
+        assert_eq!(
+            merge.conflicts,
+            vec![PokemonBreedMergeConflict::Subtree {
+                position: Position::new(1, 1),
+            }]
+        );
+        assert!(merge.tree.pokemon_nodes.contains_key(&Position::new(2, 2)));
+        assert_eq!(species(&merge.tree, Position::new(2, 3)), Some(charmander));
+        assert_eq!(
+            merge.tree.parents(Position::new(1, 1)),
+            Some((Position::new(2, 2), Position::new(2, 3)))
+        );
+
+        // Without our change to them, both parents are removed
+        let mut ours_outside = base.clone();
+        ours_outside
+            .pokemon_nodes
+            .get_mut(&Position::new(2, 1))
+            .expect("Should exist")
+            .pokemon = Some(bagon.clone());
+        let merge = PokemonBreedTreeMerge::new(&base, &ours_outside, &theirs);
+        assert!(!merge.has_conflicts());
+        assert_eq!(merge.tree.parents(Position::new(1, 1)), None);
+        assert!(!merge.tree.pokemon_nodes.contains_key(&Position::new(2, 2)));
+        assert!(!merge.tree.pokemon_nodes.contains_key(&Position::new(2, 3)));
+
+        // Both sides giving the same node a different species keeps ours
+        theirs = base.clone();
+        theirs
+            .pokemon_nodes
+            .get_mut(&Position::new(2, 1))
+            .expect("Should exist")
+            .pokemon = Some(Pokemon::ditto());
+        let merge = PokemonBreedTreeMerge::new(&base, &ours, &theirs);
+        assert_eq!(
+            merge.conflicts,
+            vec![PokemonBreedMergeConflict::Field {
+                position: Position::new(2, 1),
+                ours: PokemonBreedFieldChange::Species(None, Some(bagon.clone())),
+                theirs: PokemonBreedFieldChange::Species(None, Some(Pokemon::ditto())),
+            }]
+        );
+        assert_eq!(species(&merge.tree, Position::new(2, 1)), Some(bagon));
+        assert!(!PokemonBreedTreeMerge::new(&base, &ours, &ours).has_conflicts());
+    }
+}
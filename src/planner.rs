@@ -0,0 +1,469 @@
+#![allow(unused, dead_code)]
+use crate::cost::{PokemonBreedCosts, PokemonBreedTreeCost};
+use crate::pokedex::Pokedex;
+use crate::pokemon::{same_ivs, PokemonIv, PokemonNature};
+use crate::shopping::ShoppingList;
+use crate::target::PokemonBreedTarget;
+use crate::tree::{
+    PokemonBreedStep, PokemonBreedTree, PokemonBreedTreeError, PokemonBreedTreeNode, Position,
+};
+
+// Several trees bred at the same time. Steps refer to a tree by its index in trees.
+#[derive(Debug, Clone)]
+pub struct PokemonBreedMultiPlan {
+    pub trees: Vec<PokemonBreedTree>,
+    pub shopping_list: ShoppingList,
+    pub steps: Vec<(usize, PokemonBreedStep)>,
+    // Leaves with the same requirements across the trees, bought together
+    pub pools: Vec<PokemonBreedLeafPool>,
+    pub cost: PokemonBreedTreeCost,
+}
+
+// The same pokemon needed at several leaves, possibly in different trees
+#[derive(Debug, Clone, PartialEq)]
+pub struct PokemonBreedLeafPool {
+    pub node: PokemonBreedTreeNode,
+    pub positions: Vec<(usize, Position)>,
+}
+
+// Plans every target and puts their purchases together, so pokemon with the same requirements
+// are bought in one go. Every purchase is done first, one pool after the other, then the breeds
+// go from the pokemon with the fewest ivs to the final ones, so the breeds of the same stage in
+// every tree are done in one sitting, whatever the depth of their tree.
+pub fn plan_targets(
+    targets: &[PokemonBreedTarget],
+    pokedex: &Pokedex,
+    costs: &PokemonBreedCosts,
+) -> Result<PokemonBreedMultiPlan, PokemonBreedTreeError> {
+    let mut plan = PokemonBreedMultiPlan {
+        trees: vec![],
+        shopping_list: ShoppingList::default(),
+        steps: vec![],
+        pools: vec![],
+        cost: PokemonBreedTreeCost::default(),
+    };
+
+    for (i, target) in targets.iter().enumerate() {
+        let mut tree = PokemonBreedTree::plan(&target.node, &target.ivs_map)?;
+        tree.fill_hatch_species(pokedex)?;
+
+        plan.shopping_list
+            .merge(ShoppingList::from_tree(&tree, costs, pokedex));
+        plan.cost += costs.tree_cost(&tree);
+        plan.steps
+            .extend(tree.step_order().into_iter().map(|step| (i, step)));
+        plan.trees.push(tree);
+    }
+
+    for (i, step) in &plan.steps {
+        let PokemonBreedStep::Acquire(pos) = step else {
+            continue;
+        };
+
+        let node = &plan.trees[*i].pokemon_nodes[pos];
+        match plan
+            .pools
+            .iter_mut()
+            .find(|pool| same_requirements(&pool.node, node))
+        {
+            Some(pool) => pool.positions.push((*i, *pos)),
+            None => plan.pools.push(PokemonBreedLeafPool {
+                node: node.clone(),
+                positions: vec![(*i, *pos)],
+            }),
+        }
+    }
+
+    let pools = &plan.pools;
+    let trees = &plan.trees;
+    plan.steps.sort_by_key(|(i, step)| match step {
+        PokemonBreedStep::Acquire(pos) => {
+            let pool = pools
+                .iter()
+                .position(|pool| pool.positions.contains(&(*i, *pos)))
+                .expect("Should be pooled");
+            (0, pool, *i, *pos)
+        }
+        PokemonBreedStep::Breed(pos) => {
+            let node = &trees[*i].pokemon_nodes[pos];
+            (1, node.ivs.len() + node.nature.is_some() as usize, *i, *pos)
+        }
+    });
+
+    Ok(plan)
+}
+
+// Whether a pokemon bought for one of the leaves fits the other one as well
+fn same_requirements(a: &PokemonBreedTreeNode, b: &PokemonBreedTreeNode) -> bool {
+    a.pokemon == b.pokemon
+        && a.gender == b.gender
+        && a.nature == b.nature
+        && same_ivs(&a.ivs, &b.ivs)
+}
+
+// Something the user wants on the final pokemon, listed from the most to the least important
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PokemonBreedPriority {
+    Iv(PokemonIv),
+    Nature(PokemonNature),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PokemonBreedUpgrade {
+    pub target: PokemonBreedTarget,
+    // Money needed on top of the current plan
+    pub extra_cost: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct PokemonBreedBudgetPlan {
+    pub target: PokemonBreedTarget,
+    pub tree: PokemonBreedTree,
+    pub cost: u32,
+    // The cheapest target that gets more of the priorities, if there is one
+    pub next_upgrade: Option<PokemonBreedUpgrade>,
+}
+
+// Finds the best target for the species of the node that can be bred with the budget. Targets
+// are compared by the priorities they have, a priority is worth more than all the ones after it
+// together, so priorities are kept from the most important one down as long as they fit.
+// Every target is costed with its cheapest mix of bred and bought pokemon.
+// Returns None when not even the cheapest target fits the budget.
+pub fn plan_within_budget(
+    pokemon_node: &PokemonBreedTreeNode,
+    priorities: &[PokemonBreedPriority],
+    budget: u32,
+    pokedex: &Pokedex,
+    costs: &PokemonBreedCosts,
+) -> Result<Option<PokemonBreedBudgetPlan>, PokemonBreedTreeError> {
+    for (i, priority) in priorities.iter().enumerate() {
+        if priorities[..i].contains(priority) {
+            return Err(PokemonBreedTreeError::DuplicatePriority(i));
+        }
+    }
+
+    let mut best: Option<(PokemonBreedTarget, PokemonBreedTree, u32)> = None;
+    let mut chosen = vec![];
+    // Priorities that didn't fit, with the ones chosen before them
+    let mut dropped = vec![];
+    for priority in priorities {
+        let mut wanted = chosen.clone();
+        wanted.push(priority.clone());
+        let Some(target_node) = budget_target_node(pokemon_node, &wanted) else {
+            continue;
+        };
+
+        let (target, tree, cost) = budget_plan(target_node, pokedex, costs)?;
+        if cost <= budget {
+            chosen = wanted;
+            best = Some((target, tree, cost));
+        } else {
+            dropped.push((target, cost));
+        }
+    }
+
+    let Some((target, tree, cost)) = best else {
+        return Ok(None);
+    };
+
+    // Anything with more of the priorities has one that was dropped, along with every one
+    // chosen before it, so the cheapest of those is the next upgrade
+    let next_upgrade = dropped
+        .into_iter()
+        .min_by_key(|(_, upgrade_cost)| *upgrade_cost)
+        .map(|(target, upgrade_cost)| PokemonBreedUpgrade {
+            target,
+            extra_cost: upgrade_cost.saturating_sub(cost),
+        });
+
+    Ok(Some(PokemonBreedBudgetPlan {
+        target,
+        tree,
+        cost,
+        next_upgrade,
+    }))
+}
+
+// The node with every priority, None when they can't all be bred together
+fn budget_target_node(
+    pokemon_node: &PokemonBreedTreeNode,
+    priorities: &[PokemonBreedPriority],
+) -> Option<PokemonBreedTreeNode> {
+    let mut target_node = PokemonBreedTreeNode {
+        nature: None,
+        ivs: vec![],
+        ..pokemon_node.clone()
+    };
+
+    for priority in priorities {
+        match priority {
+            PokemonBreedPriority::Iv(iv) => target_node.ivs.push(iv.clone()),
+            PokemonBreedPriority::Nature(_) if target_node.nature.is_some() => return None,
+            PokemonBreedPriority::Nature(nature) => target_node.nature = Some(*nature),
+        }
+    }
+    if target_node.ivs.len() > 5 {
+        return None;
+    }
+
+    Some(target_node)
+}
+
+fn budget_plan(
+    target_node: PokemonBreedTreeNode,
+    pokedex: &Pokedex,
+    costs: &PokemonBreedCosts,
+) -> Result<(PokemonBreedTarget, PokemonBreedTree, u32), PokemonBreedTreeError> {
+    let target = PokemonBreedTarget::from_node(target_node);
+    let mut tree = PokemonBreedTree::plan(&target.node, &target.ivs_map)?;
+    tree.fill_hatch_species(pokedex)?;
+    costs.make_or_buy(&mut tree);
+    let cost = costs.tree_cost(&tree).total();
+
+    Ok((target, tree, cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cost::PokemonBreedCosts,
+        pokedex::Pokedex,
+        pokemon::{Pokemon, PokemonEggGroup, PokemonIv, PokemonNature, PokemonType},
+        target::PokemonBreedTarget,
+        tree::{PokemonBreedStep, PokemonBreedTreeError, PokemonBreedTreeNode, Position},
+    };
+
+    use super::{plan_targets, plan_within_budget, PokemonBreedPriority};
+
+    fn humanoid(number: u16, name: &str) -> Pokemon {
+        Pokemon {
+            number,
+            name: name.to_string(),
+            types: (PokemonType::Fighting, None),
+            egg_groups: (PokemonEggGroup::Humanoid, None),
+            percentage_male: 75.0,
+        }
+    }
+
+    #[test]
+    fn plan_targets_test() {
+        let costs = PokemonBreedCosts::default();
+        let machop = humanoid(66, "Machop");
+        let meditite = humanoid(307, "Meditite");
+        let pokedex = Pokedex::new(vec![machop.clone(), meditite.clone()]);
+        let target = |pokemon: &Pokemon| {
+            PokemonBreedTarget::from_node(PokemonBreedTreeNode {
+                pokemon: Some(pokemon.clone()),
+                gender: None,
+                nature: Some(PokemonNature::Adamant),
+                ivs: vec![PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+                evolution_note: None,
+            })
+        };
+
+        let plan = plan_targets(&[target(&machop), target(&meditite)], &pokedex, &costs)
+            .expect("Both targets can be bred");
+
+        assert_eq!(plan.trees.len(), 2);
+        assert_eq!(plan.shopping_list.total(), plan.cost.total());
+        assert_eq!(
+            plan.cost.total(),
+            plan.trees
+                .iter()
+                .map(|tree| costs.tree_cost(tree).total())
+                .sum::<u32>()
+        );
+        // Both trees need the same undecided male 1x31 attack pokemon, they're bought together
+        assert!(plan
+            .shopping_list
+            .breeders
+            .iter()
+            .any(|b| b.pokemon.is_none() && b.ivs == vec![PokemonIv::Attack] && b.count > 2));
+
+        let breeds = plan.trees.iter().map(|t| t.breed_count()).sum::<usize>();
+        assert_eq!(
+            plan.steps.len(),
+            plan.trees.iter().map(|t| t.pokemon_nodes.len()).sum()
+        );
+        assert_eq!(
+            plan.steps[plan.steps.len() - 2..],
+            [
+                (0, PokemonBreedStep::Breed(Position::new(0, 0))),
+                (1, PokemonBreedStep::Breed(Position::new(0, 0))),
+            ]
+        );
+        assert!(matches!(plan.steps[0].1, PokemonBreedStep::Acquire(_)));
+        let attack_pool = plan
+            .pools
+            .iter()
+            .find(|pool| pool.node.pokemon.is_none() && pool.node.ivs == vec![PokemonIv::Attack])
+            .expect("Should be pooled");
+        assert!(attack_pool.positions.iter().any(|(i, _)| *i == 0));
+        assert!(attack_pool.positions.iter().any(|(i, _)| *i == 1));
+        assert_eq!(
+            plan.pools
+                .iter()
+                .map(|pool| pool.positions.len())
+                .sum::<usize>(),
+            plan.steps.len() - breeds
+        );
+        assert!(matches!(
+            plan.steps[plan.steps.len() - breeds].1,
+            PokemonBreedStep::Breed(_)
+        ));
+    }
+
+    #[test]
+    fn plan_targets_depth_test() {
+        let costs = PokemonBreedCosts::default();
+        let machop = humanoid(66, "Machop");
+        let meditite = humanoid(307, "Meditite");
+        let pokedex = Pokedex::new(vec![machop.clone(), meditite.clone()]);
+        let target = |pokemon: &Pokemon, ivs: Vec<PokemonIv>| {
+            PokemonBreedTarget::from_node(PokemonBreedTreeNode {
+                pokemon: Some(pokemon.clone()),
+                gender: None,
+                nature: None,
+                ivs,
+                evolution_note: None,
+            })
+        };
+        let targets = [
+            target(&machop, vec![PokemonIv::Attack, PokemonIv::Speed]),
+            target(
+                &meditite,
+                vec![
+                    PokemonIv::Attack,
+                    PokemonIv::Speed,
+                    PokemonIv::HP,
+                    PokemonIv::Defense,
+                ],
+            ),
+        ];
+
+        let plan = plan_targets(&targets, &pokedex, &costs).expect("Both targets can be bred");
+
+        // Every 2x31 is bred before any 3x31, whatever the depth of its tree
+        let bred_ivs = plan
+            .steps
+            .iter()
+            .filter_map(|(i, step)| match step {
+                PokemonBreedStep::Breed(pos) => Some(plan.trees[*i].pokemon_nodes[pos].ivs.len()),
+                PokemonBreedStep::Acquire(_) => None,
+            })
+            .collect::<Vec<usize>>();
+        assert!(bred_ivs.is_sorted());
+        assert_eq!(bred_ivs.first(), Some(&2));
+        assert_eq!(bred_ivs.last(), Some(&4));
+        assert_eq!(
+            plan.steps
+                .iter()
+                .filter(|(i, step)| *i == 0 && matches!(step, PokemonBreedStep::Breed(_)))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn plan_within_budget_test() {
+        let costs = PokemonBreedCosts::default();
+        let machop = humanoid(66, "Machop");
+        let pokedex = Pokedex::new(vec![machop.clone()]);
+        let pokemon_node = PokemonBreedTreeNode {
+            pokemon: Some(machop),
+            gender: None,
+            nature: None,
+            ivs: vec![],
+            evolution_note: None,
+        };
+        let priorities = [
+            PokemonBreedPriority::Nature(PokemonNature::Adamant),
+            PokemonBreedPriority::Iv(PokemonIv::Attack),
+            PokemonBreedPriority::Iv(PokemonIv::Speed),
+            PokemonBreedPriority::Iv(PokemonIv::HP),
+        ];
+
+        let everything = plan_within_budget(&pokemon_node, &priorities, u32::MAX, &pokedex, &costs)
+            .expect("Should be a valid species")
+            .expect("Should fit the budget");
+        assert_eq!(everything.target.node.nature, Some(PokemonNature::Adamant));
+        assert_eq!(everything.target.node.ivs.len(), 3);
+        assert_eq!(everything.next_upgrade, None);
+
+        // A bit less money drops the least important iv, and the next upgrade brings it back
+        let plan = plan_within_budget(
+            &pokemon_node,
+            &priorities,
+            everything.cost - 1,
+            &pokedex,
+            &costs,
+        )
+        .expect("Should be a valid species")
+        .expect("Should fit the budget");
+        assert_eq!(plan.target.node.nature, Some(PokemonNature::Adamant));
+        assert_eq!(
+            plan.target.node.ivs,
+            vec![PokemonIv::Attack, PokemonIv::Speed]
+        );
+        assert_eq!(plan.cost, costs.tree_cost(&plan.tree).total());
+        let next_upgrade = plan.next_upgrade.expect("Should have an upgrade");
+        assert_eq!(next_upgrade.target, everything.target);
+        assert_eq!(next_upgrade.extra_cost, everything.cost - plan.cost);
+
+        assert!(
+            plan_within_budget(&pokemon_node, &priorities, 0, &pokedex, &costs)
+                .expect("Should be a valid species")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn plan_within_budget_priorities_test() {
+        let costs = PokemonBreedCosts::default();
+        let machop = humanoid(66, "Machop");
+        let pokedex = Pokedex::new(vec![machop.clone()]);
+        let pokemon_node = PokemonBreedTreeNode {
+            pokemon: Some(machop),
+            gender: None,
+            nature: None,
+            ivs: vec![],
+            evolution_note: None,
+        };
+
+        let duplicates = [
+            PokemonBreedPriority::Iv(PokemonIv::Attack),
+            PokemonBreedPriority::Iv(PokemonIv::Speed),
+            PokemonBreedPriority::Iv(PokemonIv::Attack),
+        ];
+        assert_eq!(
+            plan_within_budget(&pokemon_node, &duplicates, u32::MAX, &pokedex, &costs).err(),
+            Some(PokemonBreedTreeError::DuplicatePriority(2))
+        );
+
+        // Every priority is tried once, from the most important one. A sixth iv or a second
+        // nature can't be bred, so they're left out.
+        let priorities = [
+            PokemonIv::HP,
+            PokemonIv::Attack,
+            PokemonIv::Defense,
+            PokemonIv::SpecialAttack,
+            PokemonIv::SpecialDefense,
+            PokemonIv::Speed,
+        ]
+        .into_iter()
+        .map(PokemonBreedPriority::Iv)
+        .chain(
+            [PokemonNature::Adamant, PokemonNature::Jolly]
+                .into_iter()
+                .map(PokemonBreedPriority::Nature),
+        )
+        .collect::<Vec<PokemonBreedPriority>>();
+        let plan = plan_within_budget(&pokemon_node, &priorities, u32::MAX, &pokedex, &costs)
+            .expect("Should be a valid species")
+            .expect("Should fit the budget");
+        assert_eq!(plan.target.node.ivs.len(), 5);
+        assert!(!plan.target.node.ivs.contains(&PokemonIv::Speed));
+        assert_eq!(plan.target.node.nature, Some(PokemonNature::Adamant));
+        assert_eq!(plan.next_upgrade, None);
+    }
+}
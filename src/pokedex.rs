@@ -0,0 +1,112 @@
+#![allow(unused, dead_code)]
+use crate::consts::PRE_EVOLUTIONS;
+use crate::pokemon::Pokemon;
+use std::collections::HashMap;
+
+// Species data the planner works with, looked up by pokedex number
+#[derive(Debug, Default, Clone)]
+pub struct Pokedex {
+    species: HashMap<u16, Pokemon>,
+}
+
+impl Pokedex {
+    pub fn new(species: Vec<Pokemon>) -> Pokedex {
+        Pokedex {
+            species: species
+                .into_iter()
+                .map(|pokemon| (pokemon.number, pokemon))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, number: u16) -> Option<&Pokemon> {
+        self.species.get(&number)
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<&Pokemon> {
+        self.species
+            .values()
+            .find(|pokemon| pokemon.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Pokemon> {
+        self.species.values()
+    }
+
+    // The species an egg of this pokemon hatches as
+    pub fn hatch_species(&self, pokemon: &Pokemon) -> Option<&Pokemon> {
+        self.get(base_form_number(pokemon.number))
+    }
+
+    // The first form of the evolution line that can be used as a parent. Baby pokemon can't
+    // breed, so they have to be evolved before breeding again.
+    pub fn breedable_form(&self, pokemon: &Pokemon) -> Option<&Pokemon> {
+        evolution_line(pokemon.number)
+            .into_iter()
+            .filter_map(|(number, _)| self.get(number))
+            .find(|pokemon| pokemon.can_breed())
+    }
+
+    // Describes how to go from the hatched species to this pokemon, or None when the pokemon
+    // hatches as itself
+    pub fn evolution_note(&self, pokemon: &Pokemon) -> Option<String> {
+        let line = evolution_line(pokemon.number);
+        if line.len() < 2 {
+            return None;
+        }
+
+        let name = |number: u16| {
+            self.get(number)
+                .map(|pokemon| pokemon.name.clone())
+                .unwrap_or_else(|| format!("#{}", number))
+        };
+        let evolutions = line
+            .windows(2)
+            .map(|stages| {
+                let (from, _) = stages[0];
+                let (to, method) = stages[1];
+                format!(
+                    "{} evolves into {} ({})",
+                    name(from),
+                    name(to),
+                    method.unwrap_or("")
+                )
+            })
+            .collect::<Vec<String>>();
+
+        Some(format!(
+            "Hatches as {}. {}.",
+            name(line[0].0),
+            evolutions.join(", then ")
+        ))
+    }
+}
+
+pub fn base_form_number(number: u16) -> u16 {
+    let mut number = number;
+    while let Some((previous, _)) = PRE_EVOLUTIONS.get(&number) {
+        number = *previous;
+    }
+
+    number
+}
+
+// Every stage from the base form up to this pokemon, with how each stage is reached
+fn evolution_line(number: u16) -> Vec<(u16, Option<&'static str>)> {
+    let mut line = vec![(number, None)];
+    let mut number = number;
+    while let Some((previous, method)) = PRE_EVOLUTIONS.get(&number) {
+        line[0].1 = Some(*method);
+        line.insert(0, (*previous, None));
+        number = *previous;
+    }
+
+    line
+}
+
+pub fn is_in_evolution_line(number: u16) -> bool {
+    PRE_EVOLUTIONS.contains_key(&number)
+        || PRE_EVOLUTIONS
+            .values()
+            .any(|(previous, _)| *previous == number)
+}
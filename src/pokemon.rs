@@ -1,4 +1,7 @@
 #![allow(unused, dead_code)]
+use crate::consts::{DITTO_NUMBER, GENDERLESS_POKEMON_EVOLUTION_TREE};
+use crate::pokedex::base_form_number;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PokemonType {
     Fire,
@@ -79,6 +82,37 @@ pub enum PokemonIv {
     Speed,
 }
 
+// Breeding doesn't care about the order of the ivs, so two lists with the same ivs are the same
+pub fn same_ivs(a: &[PokemonIv], b: &[PokemonIv]) -> bool {
+    a.iter().all(|iv| b.contains(iv)) && b.iter().all(|iv| a.contains(iv))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PokemonHeldItem {
+    PowerWeight,
+    PowerBracer,
+    PowerBelt,
+    PowerLens,
+    PowerBand,
+    PowerAnklet,
+    Everstone,
+    Other(String),
+}
+
+impl PokemonHeldItem {
+    // The power item that makes a parent pass down this iv
+    pub fn power_item(iv: &PokemonIv) -> PokemonHeldItem {
+        match iv {
+            PokemonIv::HP => PokemonHeldItem::PowerWeight,
+            PokemonIv::Attack => PokemonHeldItem::PowerBracer,
+            PokemonIv::Defense => PokemonHeldItem::PowerBelt,
+            PokemonIv::SpecialAttack => PokemonHeldItem::PowerLens,
+            PokemonIv::SpecialDefense => PokemonHeldItem::PowerBand,
+            PokemonIv::Speed => PokemonHeldItem::PowerAnklet,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PokemonGender {
     Female,
@@ -96,6 +130,79 @@ pub struct Pokemon {
 }
 
 impl Pokemon {
+    pub fn ditto() -> Pokemon {
+        Pokemon {
+            number: DITTO_NUMBER,
+            name: "Ditto".to_string(),
+            types: (PokemonType::Normal, None),
+            egg_groups: (PokemonEggGroup::Ditto, None),
+            percentage_male: 0.0,
+        }
+    }
+
+    pub fn is_ditto(&self) -> bool {
+        self.egg_groups_include(&PokemonEggGroup::Ditto)
+    }
+
+    pub fn is_genderless(&self) -> bool {
+        self.egg_groups_include(&PokemonEggGroup::Genderless)
+    }
+
+    pub fn is_male_only(&self) -> bool {
+        !self.is_ditto() && !self.is_genderless() && self.percentage_male >= 100.0
+    }
+
+    pub fn is_female_only(&self) -> bool {
+        !self.is_ditto() && !self.is_genderless() && self.percentage_male <= 0.0
+    }
+
+    pub fn can_breed(&self) -> bool {
+        !self.egg_groups_include(&PokemonEggGroup::CannotBreed)
+    }
+
+    // Chance (0 to 1) of an egg of this species hatching with the given gender
+    pub fn gender_chance(&self, gender: &PokemonGender) -> f32 {
+        match gender {
+            PokemonGender::Male => self.percentage_male / 100.0,
+            PokemonGender::Female => 1.0 - self.percentage_male / 100.0,
+            PokemonGender::Genderless => {
+                if self.is_genderless() || self.is_ditto() {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    // Whether this pokemon can pass down the species of the other one, which is true for any
+    // pokemon of the same evolution line. Genderless lines are listed apart since they're
+    // planned without a pokedex.
+    pub fn is_same_family(&self, other: &Pokemon) -> bool {
+        if self.number == other.number {
+            return true;
+        }
+
+        match (
+            GENDERLESS_POKEMON_EVOLUTION_TREE.get(&self.number),
+            GENDERLESS_POKEMON_EVOLUTION_TREE.get(&other.number),
+        ) {
+            (Some(tree), Some(other_tree)) => tree == other_tree,
+            _ => base_form_number(self.number) == base_form_number(other.number),
+        }
+    }
+
+    pub fn shares_egg_group(&self, other: &Pokemon) -> bool {
+        let shares = |egg_group: &PokemonEggGroup| {
+            *egg_group != PokemonEggGroup::Genderless && other.egg_groups_include(egg_group)
+        };
+
+        match self.egg_groups.1 {
+            Some(ref egg_group2) => shares(&self.egg_groups.0) || shares(egg_group2),
+            None => shares(&self.egg_groups.0),
+        }
+    }
+
     pub fn egg_groups_include(&self, egg_group: &PokemonEggGroup) -> bool {
         match self.egg_groups.1 {
             Some(ref egg_group2) => self.egg_groups.0 == *egg_group || *egg_group2 == *egg_group,
@@ -0,0 +1,459 @@
+#![allow(unused, dead_code)]
+use crate::cost::PokemonBreedCosts;
+use crate::pokedex::Pokedex;
+use crate::pokemon::{same_ivs, Pokemon, PokemonGender, PokemonHeldItem, PokemonIv, PokemonNature};
+use crate::tree::{PokemonBreedTree, PokemonBreedTreeNode, PokemonBreederKind, Position};
+
+// How many species are suggested for a pokemon without one
+const SUGGESTED_SPECIES_COUNT: usize = 5;
+
+// Pokemon that have to be bought before breeding. Pokemon with the same requirements are
+// grouped, a missing species means any species that can breed with its partner works.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingListBreeder {
+    pub pokemon: Option<Pokemon>,
+    // None for pokemon with more than one iv
+    pub kind: Option<PokemonBreederKind>,
+    pub gender: Option<PokemonGender>,
+    pub nature: Option<PokemonNature>,
+    pub ivs: Vec<PokemonIv>,
+    // Species that fit when no species is set, the ones most likely to have the gender first
+    pub suggested_species: Vec<Pokemon>,
+    pub count: u32,
+    // Price of a single pokemon
+    pub price: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShoppingListItem {
+    pub item: PokemonHeldItem,
+    pub count: u32,
+    // Price of a single item
+    pub price: u32,
+    // Items held in the breeds of each stage, by the number of ivs and nature of the bred
+    // pokemon. The breeds of a stage can be done at the same time, even in different trees.
+    pub held_per_stage: Vec<u32>,
+}
+
+impl ShoppingListItem {
+    // Items needed at the same time when every breed of a stage is done in one go
+    pub fn held_at_once(&self) -> u32 {
+        self.held_per_stage.iter().copied().max().unwrap_or(0)
+    }
+}
+
+// Everything to buy to complete one or more trees. Items are used up on every breed, so one
+// is needed per parent. Picking the gender of the offspring is paid when breeding, so it's
+// only kept as a total.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShoppingList {
+    pub breeders: Vec<ShoppingListBreeder>,
+    pub items: Vec<ShoppingListItem>,
+    pub genders: u32,
+}
+
+impl ShoppingList {
+    // Pokemon the user already owns aren't bought, and neither is anything needed to breed them
+    pub fn from_tree(
+        tree: &PokemonBreedTree,
+        costs: &PokemonBreedCosts,
+        pokedex: &Pokedex,
+    ) -> ShoppingList {
+        let mut shopping_list = ShoppingList {
+            genders: costs.tree_cost(tree).genders,
+            ..Default::default()
+        };
+
+        let mut positions = tree
+            .pokemon_nodes
+            .keys()
+            .copied()
+            .collect::<Vec<Position>>();
+        positions.sort();
+        for pos in positions {
+            let node = &tree.pokemon_nodes[&pos];
+            if tree.owned.contains(&pos) {
+                continue;
+            }
+
+            if !tree.is_bred(pos) {
+                let price = costs.leaf_price(node);
+                shopping_list.add_breeder(ShoppingListBreeder {
+                    pokemon: node.pokemon.clone(),
+                    kind: tree.breeder_kind(pos),
+                    gender: node.gender.clone(),
+                    nature: node.nature,
+                    ivs: node.ivs.clone(),
+                    suggested_species: suggested_species(tree, pos, pokedex),
+                    count: 1,
+                    price,
+                });
+                continue;
+            }
+
+            let (p1_pos, p2_pos) = pos.get_parent_positions();
+            let p1 = tree.pokemon_nodes.get(&p1_pos).expect("Should exist");
+            let p2 = tree.pokemon_nodes.get(&p2_pos).expect("Should exist");
+            for item in breed_items(node, p1, p2) {
+                let price = match item {
+                    PokemonHeldItem::Everstone => costs.everstone,
+                    _ => costs.power_item,
+                };
+                let stage = node.ivs.len() + node.nature.is_some() as usize;
+                let mut held_per_stage = vec![0; stage + 1];
+                held_per_stage[stage] = 1;
+                shopping_list.add_item(ShoppingListItem {
+                    item,
+                    count: 1,
+                    price,
+                    held_per_stage,
+                });
+            }
+        }
+
+        shopping_list
+            .breeders
+            .sort_by_key(|breeder| (breeder.ivs.len(), breeder.nature.is_none()));
+
+        shopping_list
+    }
+
+    pub fn add_breeder(&mut self, breeder: ShoppingListBreeder) {
+        let same_breeder = self.breeders.iter_mut().find(|b| {
+            b.pokemon == breeder.pokemon
+                && b.gender == breeder.gender
+                && b.nature == breeder.nature
+                && same_ivs(&b.ivs, &breeder.ivs)
+                && b.price == breeder.price
+        });
+
+        match same_breeder {
+            Some(b) => {
+                b.count += breeder.count;
+                // Only the species that fit every one of the grouped pokemon are suggested
+                b.suggested_species
+                    .retain(|pokemon| breeder.suggested_species.contains(pokemon));
+            }
+            None => self.breeders.push(breeder),
+        }
+    }
+
+    pub fn add_item(&mut self, item: ShoppingListItem) {
+        let Some(same_item) = self.items.iter_mut().find(|i| i.item == item.item) else {
+            self.items.push(item);
+            return;
+        };
+
+        same_item.count += item.count;
+        if same_item.held_per_stage.len() < item.held_per_stage.len() {
+            same_item
+                .held_per_stage
+                .resize(item.held_per_stage.len(), 0);
+        }
+        for (stage, held) in item.held_per_stage.into_iter().enumerate() {
+            same_item.held_per_stage[stage] += held;
+        }
+    }
+
+    // Adds everything from the other list to this one, grouping what both lists need
+    pub fn merge(&mut self, other: ShoppingList) {
+        for breeder in other.breeders {
+            self.add_breeder(breeder);
+        }
+        for item in other.items {
+            self.add_item(item);
+        }
+        self.genders += other.genders;
+    }
+
+    pub fn breeders_total(&self) -> u32 {
+        self.breeders.iter().map(|b| b.count * b.price).sum()
+    }
+
+    pub fn items_total(&self) -> u32 {
+        self.items.iter().map(|i| i.count * i.price).sum()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.breeders_total() + self.items_total() + self.genders
+    }
+}
+
+// Species that can be bought for a pokemon without one. They have to be able to have its
+// gender and to breed with its partner. A female passes its species down to its child, so it's
+// checked against the species of the female line instead, and its male partner is then
+// suggested species that breed with the first one suggested for it.
+fn suggested_species(
+    tree: &PokemonBreedTree,
+    position: Position,
+    pokedex: &Pokedex,
+) -> Vec<Pokemon> {
+    let node = &tree.pokemon_nodes[&position];
+    if node.pokemon.is_some() {
+        return vec![];
+    }
+    // The female line carries the species, it can't be any other
+    if position.is_female_line() {
+        return tree.species_at(position).cloned().into_iter().collect();
+    }
+    let Some(female_line_species) = tree
+        .species_at(Position::new(1, 0))
+        .or(tree.species_at(Position::new(0, 0)))
+    else {
+        return vec![];
+    };
+
+    // A female is the left one of her pair
+    let partner_pos = position.get_partner_position();
+    let partner_species = if partner_pos.col() > position.col() {
+        None
+    } else {
+        pair_species(tree, partner_pos, pokedex).filter(|pokemon| !pokemon.is_ditto())
+    };
+    let breeds_with = partner_species.as_ref().unwrap_or(female_line_species);
+
+    let mut species = pokedex
+        .iter()
+        .filter(|pokemon| {
+            pokemon.can_breed()
+                && !pokemon.is_ditto()
+                && pokemon.shares_egg_group(breeds_with)
+                && pokedex.hatch_species(pokemon) == Some(*pokemon)
+        })
+        .map(|pokemon| {
+            let chance = node
+                .gender
+                .as_ref()
+                .map_or(1.0, |gender| pokemon.gender_chance(gender));
+            (chance, pokemon)
+        })
+        .filter(|(chance, _)| *chance > 0.0)
+        .collect::<Vec<(f32, &Pokemon)>>();
+    species.sort_by(|(c1, p1), (c2, p2)| c2.total_cmp(c1).then(p1.number.cmp(&p2.number)));
+
+    species
+        .into_iter()
+        .take(SUGGESTED_SPECIES_COUNT)
+        .map(|(_, pokemon)| pokemon.clone())
+        .collect()
+}
+
+// The species the pokemon at this position ends up with: its own, the one of its female parent
+// when it's bred, or the first one suggested for it
+fn pair_species(tree: &PokemonBreedTree, position: Position, pokedex: &Pokedex) -> Option<Pokemon> {
+    let mut position = position;
+    loop {
+        if let Some(pokemon) = tree.species_at(position) {
+            return Some(pokemon.clone());
+        }
+        if !tree.is_bred(position) {
+            return suggested_species(tree, position, pokedex)
+                .into_iter()
+                .next();
+        }
+
+        position = position.get_parent_positions().0;
+    }
+}
+
+// The items the parents hold to breed the child: an everstone on the parent passing down the
+// nature, and a power item for an iv that the other parent doesn't have
+pub fn breed_items(
+    child: &PokemonBreedTreeNode,
+    p1: &PokemonBreedTreeNode,
+    p2: &PokemonBreedTreeNode,
+) -> Vec<PokemonHeldItem> {
+    let nature_parent = match child.nature {
+        Some(_) if p1.nature == child.nature => Some(0),
+        Some(_) if p2.nature == child.nature => Some(1),
+        _ => None,
+    };
+
+    [(p1, p2), (p2, p1)]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (parent, partner))| {
+            if nature_parent == Some(i) {
+                return Some(PokemonHeldItem::Everstone);
+            }
+
+            parent
+                .ivs
+                .iter()
+                .find(|iv| child.ivs.contains(iv) && !partner.ivs.contains(iv))
+                .or_else(|| parent.ivs.iter().find(|iv| child.ivs.contains(iv)))
+                .or(child.ivs.first())
+                .map(PokemonHeldItem::power_item)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        cost::PokemonBreedCosts,
+        fixtures::{charizard, charmander, final_node},
+        pokedex::Pokedex,
+        pokemon::{
+            Pokemon, PokemonEggGroup, PokemonGender, PokemonHeldItem, PokemonIv, PokemonNature,
+            PokemonType,
+        },
+        tree::{PokemonBreedTree, PokemonBreedTreeNode, PokemonBreederKind, Position},
+    };
+
+    use super::{suggested_species, ShoppingList};
+
+    #[test]
+    fn shopping_list_from_tree_test() {
+        let costs = PokemonBreedCosts::default();
+        let charmander = charmander();
+        let charizard = charizard();
+        let bulbasaur = Pokemon {
+            number: 1,
+            name: "Bulbasaur".to_string(),
+            types: (PokemonType::Grass, Some(PokemonType::Poison)),
+            egg_groups: (PokemonEggGroup::Monster, Some(PokemonEggGroup::Plant)),
+            percentage_male: 87.5,
+        };
+        let dratini = Pokemon {
+            number: 147,
+            name: "Dratini".to_string(),
+            types: (PokemonType::Dragon, None),
+            egg_groups: (PokemonEggGroup::WaterA, Some(PokemonEggGroup::Dragon)),
+            percentage_male: 50.0,
+        };
+        let pokedex = Pokedex::new(vec![
+            charmander.clone(),
+            charizard.clone(),
+            bulbasaur.clone(),
+            dratini.clone(),
+            Pokemon::ditto(),
+        ]);
+        let final_pokemon_node = final_node(
+            charizard,
+            &[PokemonIv::Attack, PokemonIv::Speed],
+            Some(PokemonNature::Adamant),
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        tree.fill_hatch_species(&pokedex)
+            .expect("Should be in the pokedex");
+        let shopping_list = ShoppingList::from_tree(&tree, &costs, &pokedex);
+
+        assert_eq!(shopping_list.total(), costs.tree_cost(&tree).total());
+        assert_eq!(
+            shopping_list.breeders.iter().map(|b| b.count).sum::<u32>(),
+            4
+        );
+        let nature_breeder = &shopping_list.breeders[0];
+        assert_eq!(nature_breeder.kind, Some(PokemonBreederKind::Nature));
+        assert_eq!(nature_breeder.pokemon, Some(charmander.clone()));
+        assert_eq!(nature_breeder.gender, Some(PokemonGender::Female));
+
+        // Any male of the egg groups of Charmander can pass the attack iv, starting with the
+        // species where males are the most common
+        let male_attack = shopping_list
+            .breeders
+            .iter()
+            .find(|b| {
+                b.kind == Some(PokemonBreederKind::A) && b.gender == Some(PokemonGender::Male)
+            })
+            .expect("Should need a male attack breeder");
+        assert_eq!(male_attack.pokemon, None);
+        assert_eq!(
+            male_attack.suggested_species,
+            vec![bulbasaur, charmander, dratini]
+        );
+
+        let item = |item: PokemonHeldItem| {
+            shopping_list
+                .items
+                .iter()
+                .find(|i| i.item == item)
+                .expect("Should be needed")
+        };
+        assert_eq!(item(PokemonHeldItem::Everstone).count, 2);
+        assert_eq!(item(PokemonHeldItem::Everstone).held_at_once(), 1);
+        assert_eq!(item(PokemonHeldItem::PowerBracer).count, 2);
+        assert_eq!(item(PokemonHeldItem::PowerBracer).held_at_once(), 2);
+        assert_eq!(item(PokemonHeldItem::PowerAnklet).count, 2);
+        assert_eq!(item(PokemonHeldItem::PowerAnklet).held_at_once(), 1);
+    }
+
+    #[test]
+    fn shopping_list_pairs_test() {
+        let costs = PokemonBreedCosts::default();
+        let charmander = charmander();
+        let bulbasaur = Pokemon {
+            number: 1,
+            name: "Bulbasaur".to_string(),
+            types: (PokemonType::Grass, Some(PokemonType::Poison)),
+            egg_groups: (PokemonEggGroup::Monster, Some(PokemonEggGroup::Plant)),
+            percentage_male: 87.5,
+        };
+        let dratini = Pokemon {
+            number: 147,
+            name: "Dratini".to_string(),
+            types: (PokemonType::Dragon, None),
+            egg_groups: (PokemonEggGroup::WaterA, Some(PokemonEggGroup::Dragon)),
+            percentage_male: 50.0,
+        };
+        let pokedex = Pokedex::new(vec![charmander.clone(), bulbasaur.clone(), dratini.clone()]);
+        let tree_for = |ivs: Vec<PokemonIv>| {
+            let final_pokemon_node = PokemonBreedTreeNode {
+                ivs: ivs.clone(),
+                pokemon: Some(charmander.clone()),
+                gender: None,
+                nature: None,
+                evolution_note: None,
+            };
+            let kinds = [
+                PokemonBreederKind::A,
+                PokemonBreederKind::B,
+                PokemonBreederKind::C,
+                PokemonBreederKind::D,
+            ];
+            let final_iv_map = kinds.into_iter().zip(ivs).collect();
+            PokemonBreedTree::new(&final_pokemon_node, &final_iv_map)
+        };
+        let ivs = vec![
+            PokemonIv::Attack,
+            PokemonIv::Speed,
+            PokemonIv::HP,
+            PokemonIv::Defense,
+        ];
+        let tree = tree_for(ivs.clone());
+
+        // The female is most likely a Dratini, so her partner has to breed with a Dratini and
+        // Bulbasaur doesn't fit even if it breeds with Charmander
+        let female = suggested_species(&tree, Position::new(3, 2), &pokedex);
+        assert_eq!(female[0], dratini);
+        let male = suggested_species(&tree, Position::new(3, 3), &pokedex);
+        assert!(!male.is_empty());
+        assert!(!male.contains(&bulbasaur));
+        assert!(male
+            .iter()
+            .all(|pokemon| pokemon.shares_egg_group(&female[0])));
+
+        // The 2x31 breeds of both trees are done in the same sitting, whatever their row
+        let mut shopping_list =
+            ShoppingList::from_tree(&tree_for(ivs[..2].to_vec()), &costs, &pokedex);
+        shopping_list.merge(ShoppingList::from_tree(
+            &tree_for(ivs[..3].to_vec()),
+            &costs,
+            &pokedex,
+        ));
+        let power_bracer = shopping_list
+            .items
+            .iter()
+            .find(|i| i.item == PokemonHeldItem::PowerBracer)
+            .expect("Should be needed");
+        assert_eq!(power_bracer.held_per_stage[2], 3);
+        assert_eq!(power_bracer.held_at_once(), 3);
+    }
+}
@@ -0,0 +1,298 @@
+#![allow(unused, dead_code)]
+use crate::pokedex::{is_in_evolution_line, Pokedex};
+use crate::pokemon::{Pokemon, PokemonGender, PokemonIv, PokemonNature};
+use crate::tree::{ivs_map, FinalPokemonIvsMap, PokemonBreedTreeNode};
+
+// A pokemon can't know more than 4 moves
+const MAX_EGG_MOVES: usize = 4;
+
+// What the user wants to breed. The node and ivs map are what the tree is planned from, the
+// ability and egg moves are kept for the user, the planner doesn't handle them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PokemonBreedTarget {
+    pub node: PokemonBreedTreeNode,
+    pub ivs_map: FinalPokemonIvsMap,
+    pub ability: Option<String>,
+    pub egg_moves: Vec<String>,
+}
+
+impl PokemonBreedTarget {
+    pub fn builder(pokedex: &Pokedex) -> PokemonBreedTargetBuilder<'_> {
+        PokemonBreedTargetBuilder {
+            pokedex,
+            species: None,
+            ivs: vec![],
+            nature: None,
+            gender: None,
+            ability: None,
+            egg_moves: vec![],
+        }
+    }
+
+    // The ivs are given to the breeder kinds in the order of the node ivs
+    pub fn from_node(node: PokemonBreedTreeNode) -> PokemonBreedTarget {
+        PokemonBreedTarget {
+            ivs_map: ivs_map(&node.ivs),
+            node,
+            ability: None,
+            egg_moves: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PokemonBreedTargetError {
+    // No species was given
+    MissingSpecies,
+    UnknownSpeciesName(String),
+    UnknownSpeciesNumber(u16),
+    // The species can't be obtained from an egg
+    CannotBreed(u16),
+    // There has to be at least one iv or a nature to breed for
+    NothingToBreed,
+    DuplicateIv(PokemonIv),
+    // Breeding charts only go up to 5 ivs
+    TooManyIvs(usize),
+    UnavailableGender(PokemonGender),
+    TooManyEggMoves(usize),
+}
+
+impl std::fmt::Display for PokemonBreedTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PokemonBreedTargetError::MissingSpecies => write!(f, "The target needs a species"),
+            PokemonBreedTargetError::UnknownSpeciesName(name) => {
+                write!(f, "There is no pokemon named {} in the pokedex", name)
+            }
+            PokemonBreedTargetError::UnknownSpeciesNumber(number) => {
+                write!(f, "Pokemon #{} isn't in the pokedex", number)
+            }
+            PokemonBreedTargetError::CannotBreed(number) => {
+                write!(f, "Pokemon #{} can't be obtained by breeding", number)
+            }
+            PokemonBreedTargetError::NothingToBreed => {
+                write!(f, "The target needs at least one iv or a nature")
+            }
+            PokemonBreedTargetError::DuplicateIv(iv) => {
+                write!(f, "The {:?} iv was given more than once", iv)
+            }
+            PokemonBreedTargetError::TooManyIvs(count) => write!(
+                f,
+                "A pokemon with {} ivs can't be bred, the most is 5 ivs",
+                count
+            ),
+            PokemonBreedTargetError::UnavailableGender(gender) => {
+                write!(f, "The species can't be {:?}", gender)
+            }
+            PokemonBreedTargetError::TooManyEggMoves(count) => write!(
+                f,
+                "A pokemon can't know {} egg moves, the most is {}",
+                count, MAX_EGG_MOVES
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PokemonBreedTargetError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SpeciesQuery {
+    Name(String),
+    Number(u16),
+}
+
+// Builds a target step by step. Nothing is checked until the target is built.
+#[derive(Debug, Clone)]
+pub struct PokemonBreedTargetBuilder<'a> {
+    pokedex: &'a Pokedex,
+    species: Option<SpeciesQuery>,
+    ivs: Vec<PokemonIv>,
+    nature: Option<PokemonNature>,
+    gender: Option<PokemonGender>,
+    ability: Option<String>,
+    egg_moves: Vec<String>,
+}
+
+impl PokemonBreedTargetBuilder<'_> {
+    pub fn species_name(mut self, name: &str) -> Self {
+        self.species = Some(SpeciesQuery::Name(name.to_string()));
+        self
+    }
+
+    pub fn species_number(mut self, number: u16) -> Self {
+        self.species = Some(SpeciesQuery::Number(number));
+        self
+    }
+
+    pub fn iv(mut self, iv: PokemonIv) -> Self {
+        self.ivs.push(iv);
+        self
+    }
+
+    pub fn ivs(mut self, ivs: &[PokemonIv]) -> Self {
+        self.ivs.extend(ivs.iter().cloned());
+        self
+    }
+
+    pub fn nature(mut self, nature: PokemonNature) -> Self {
+        self.nature = Some(nature);
+        self
+    }
+
+    pub fn gender(mut self, gender: PokemonGender) -> Self {
+        self.gender = Some(gender);
+        self
+    }
+
+    pub fn ability(mut self, ability: &str) -> Self {
+        self.ability = Some(ability.to_string());
+        self
+    }
+
+    pub fn egg_move(mut self, egg_move: &str) -> Self {
+        self.egg_moves.push(egg_move.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<PokemonBreedTarget, PokemonBreedTargetError> {
+        let pokemon = match self.species {
+            None => return Err(PokemonBreedTargetError::MissingSpecies),
+            Some(SpeciesQuery::Name(ref name)) => self
+                .pokedex
+                .get_by_name(name)
+                .ok_or(PokemonBreedTargetError::UnknownSpeciesName(name.clone()))?,
+            Some(SpeciesQuery::Number(number)) => self
+                .pokedex
+                .get(number)
+                .ok_or(PokemonBreedTargetError::UnknownSpeciesNumber(number))?,
+        };
+        let breedable = pokemon.can_breed() || is_in_evolution_line(pokemon.number);
+        if pokemon.is_ditto() || !breedable {
+            return Err(PokemonBreedTargetError::CannotBreed(pokemon.number));
+        }
+
+        if self.ivs.is_empty() && self.nature.is_none() {
+            return Err(PokemonBreedTargetError::NothingToBreed);
+        }
+        for (i, iv) in self.ivs.iter().enumerate() {
+            if self.ivs[..i].contains(iv) {
+                return Err(PokemonBreedTargetError::DuplicateIv(iv.clone()));
+            }
+        }
+        if self.ivs.len() > 5 {
+            return Err(PokemonBreedTargetError::TooManyIvs(self.ivs.len()));
+        }
+
+        if let Some(ref gender) = self.gender {
+            if pokemon.gender_chance(gender) <= 0.0 {
+                return Err(PokemonBreedTargetError::UnavailableGender(gender.clone()));
+            }
+        }
+        if self.egg_moves.len() > MAX_EGG_MOVES {
+            return Err(PokemonBreedTargetError::TooManyEggMoves(
+                self.egg_moves.len(),
+            ));
+        }
+
+        Ok(PokemonBreedTarget {
+            ivs_map: ivs_map(&self.ivs),
+            node: PokemonBreedTreeNode {
+                pokemon: Some(pokemon.clone()),
+                gender: self.gender,
+                nature: self.nature,
+                ivs: self.ivs,
+                evolution_note: None,
+            },
+            ability: self.ability,
+            egg_moves: self.egg_moves,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pokedex::Pokedex,
+        pokemon::{Pokemon, PokemonEggGroup, PokemonGender, PokemonIv, PokemonNature, PokemonType},
+        tree::{PokemonBreedTree, PokemonBreederKind},
+    };
+
+    use super::{PokemonBreedTarget, PokemonBreedTargetError};
+
+    #[test]
+    fn target_builder_test() {
+        let tauros = Pokemon {
+            number: 128,
+            name: "Tauros".to_string(),
+            types: (PokemonType::Normal, None),
+            egg_groups: (PokemonEggGroup::Field, None),
+            percentage_male: 100.0,
+        };
+        let pokedex = Pokedex::new(vec![tauros.clone(), Pokemon::ditto()]);
+
+        let target = PokemonBreedTarget::builder(&pokedex)
+            .species_name("tauros")
+            .iv(PokemonIv::Attack)
+            .iv(PokemonIv::Speed)
+            .nature(PokemonNature::Jolly)
+            .gender(PokemonGender::Male)
+            .ability("Intimidate")
+            .egg_move("Curse")
+            .build()
+            .expect("Should be a valid target");
+
+        assert_eq!(target.node.pokemon, Some(tauros));
+        assert_eq!(target.node.nature, Some(PokemonNature::Jolly));
+        assert_eq!(target.ivs_map[&PokemonBreederKind::B], PokemonIv::Speed);
+        assert_eq!(target.ability.as_deref(), Some("Intimidate"));
+        let tree =
+            PokemonBreedTree::plan(&target.node, &target.ivs_map).expect("Should plan the target");
+        assert_eq!(tree.get_final_pokemon_node(), &target.node);
+
+        let builder = PokemonBreedTarget::builder(&pokedex).species_number(128);
+        assert_eq!(
+            builder.clone().nature(PokemonNature::Jolly).build().err(),
+            None
+        );
+        assert_eq!(
+            builder.clone().build().err(),
+            Some(PokemonBreedTargetError::NothingToBreed)
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .ivs(&[PokemonIv::HP, PokemonIv::HP])
+                .build()
+                .err(),
+            Some(PokemonBreedTargetError::DuplicateIv(PokemonIv::HP))
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .iv(PokemonIv::HP)
+                .gender(PokemonGender::Female)
+                .build()
+                .err(),
+            Some(PokemonBreedTargetError::UnavailableGender(
+                PokemonGender::Female
+            ))
+        );
+        assert_eq!(
+            PokemonBreedTarget::builder(&pokedex)
+                .species_name("Ditto")
+                .iv(PokemonIv::HP)
+                .build()
+                .err(),
+            Some(PokemonBreedTargetError::CannotBreed(132))
+        );
+        assert_eq!(
+            PokemonBreedTarget::builder(&pokedex)
+                .species_name("Missingno")
+                .build()
+                .err(),
+            Some(PokemonBreedTargetError::UnknownSpeciesName(
+                "Missingno".to_string()
+            ))
+        );
+    }
+}
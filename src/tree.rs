@@ -1,6 +1,10 @@
 #![allow(unused, dead_code)]
-use crate::pokemon::{Pokemon, PokemonGender, PokemonIv, PokemonNature};
-use std::collections::HashMap;
+use crate::consts::GENDERLESS_POKEMON_EVOLUTION_TREE;
+use crate::cost::PokemonBreedCosts;
+use crate::diff::{node_changes, PokemonBreedFieldChange};
+use crate::pokedex::{base_form_number, is_in_evolution_line, Pokedex};
+use crate::pokemon::{same_ivs, Pokemon, PokemonGender, PokemonIv, PokemonNature};
+use std::collections::{HashMap, HashSet};
 
 /* In Pokemmo, in breeding, you can only breed a pokemon couple once.
  You lose the parents on a breed, and receive the offspring.
@@ -28,6 +32,28 @@ pub struct PokemonBreedTreePosition {
 pub struct Position(u8, u8);
 
 impl Position {
+    pub fn new(row: u8, col: u8) -> Position {
+        Position(row, col)
+    }
+
+    pub fn row(self) -> u8 {
+        self.0
+    }
+
+    pub fn col(self) -> u8 {
+        self.1
+    }
+
+    pub fn is_root(self) -> bool {
+        self == Position(0, 0)
+    }
+
+    // The female line is the chain of nodes that carries the species down to the final pokemon.
+    // Every even column is the female of its pair, so the female line is the first column.
+    pub fn is_female_line(self) -> bool {
+        self.1 == 0
+    }
+
     pub fn get_partner_position(self) -> Position {
         let Position(row, col) = self;
 
@@ -36,6 +62,52 @@ impl Position {
         Position(row, partner_col)
     }
 
+    // The position of the pokemon bred from this one and its partner, None for the root
+    pub fn get_child_position(self) -> Option<Position> {
+        let Position(row, col) = self;
+
+        if row == 0 {
+            return None;
+        }
+
+        Some(Position(row - 1, col / 2))
+    }
+
+    // Takes this position as part of a subtree rooted at (0, 0) and returns where it ends up
+    // when that subtree is rooted at the given position instead, None when it would go past
+    // the last row
+    fn moved_under(self, root: Position) -> Option<Position> {
+        let Position(row, col) = self;
+        let Position(root_row, root_col) = root;
+        let row = root_row as u32 + row as u32;
+        if row > MAX_POSITION_ROW as u32 {
+            return None;
+        }
+
+        let col = ((root_col as u32) << (row - root_row as u32)) + col as u32;
+        Some(Position(row as u8, col as u8))
+    }
+
+    // The opposite of moved_under: where this position is in the subtree rooted at the given
+    // position, once that subtree is rooted at (0, 0)
+    fn relative_to(self, root: Position) -> Position {
+        let Position(row, col) = self;
+        let Position(root_row, root_col) = root;
+        let row = row - root_row;
+
+        Position(row, (col as u32 - ((root_col as u32) << row)) as u8)
+    }
+
+    // Whether this position is the given one or one of the positions bred to obtain it
+    pub fn is_in_subtree_of(self, root: Position) -> bool {
+        let Position(row, col) = self;
+        let Position(root_row, root_col) = root;
+
+        // Rows can be further apart than the bits of a column
+        let shifted = (col as u32).checked_shr(row.saturating_sub(root_row) as u32);
+        row >= root_row && shifted.unwrap_or(0) == root_col as u32
+    }
+
     // Warn: this can return invalid positions
     // Always check if the rows returned here are bigger than the generaions nr
     pub fn get_parent_positions(self) -> (Position, Position) {
@@ -49,6 +121,119 @@ impl Position {
 
         (parent1, parent2)
     }
+
+    // The parents to go through from the final pokemon to reach this position, like
+    // "root → female → male". The female is the first parent of each pair.
+    pub fn to_path(self) -> String {
+        let Position(row, col) = self;
+        let mut path = vec!["root"];
+        for step in (0..row).rev() {
+            let bit = (col as u32).checked_shr(step as u32).unwrap_or(0) & 1;
+            path.push(if bit == 0 { "female" } else { "male" });
+        }
+
+        path.join(" → ")
+    }
+
+    pub fn from_path(path: &str) -> Result<Position, PositionParseError> {
+        let invalid_path = || PositionParseError::InvalidPath(path.to_string());
+        let mut steps = path
+            .split(['→', '>', '/'])
+            .map(|step| step.trim_matches(|c: char| c.is_whitespace() || c == '-'));
+        if !steps
+            .next()
+            .is_some_and(|step| step.eq_ignore_ascii_case("root"))
+        {
+            return Err(invalid_path());
+        }
+
+        let mut position = Position(0, 0);
+        for step in steps {
+            if position.0 >= MAX_POSITION_ROW {
+                return Err(invalid_path());
+            }
+
+            let (female, male) = position.get_parent_positions();
+            position = if step.eq_ignore_ascii_case("female") {
+                female
+            } else if step.eq_ignore_ascii_case("male") {
+                male
+            } else {
+                return Err(invalid_path());
+            };
+        }
+
+        Ok(position)
+    }
+
+    // Generations and pokemon are counted from 1, "Gen 1 #1" being the final pokemon
+    pub fn to_label(self) -> String {
+        format!("Gen {} #{}", self.0 as u16 + 1, self.1 as u16 + 1)
+    }
+
+    pub fn from_label(label: &str) -> Result<Position, PositionParseError> {
+        let invalid_label = || PositionParseError::InvalidLabel(label.to_string());
+        let (generation, number) = label
+            .trim()
+            .strip_prefix("Gen")
+            .and_then(|label| label.split_once('#'))
+            .ok_or_else(invalid_label)?;
+        let generation = generation
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| invalid_label())?;
+        let number = number.trim().parse::<u16>().map_err(|_| invalid_label())?;
+
+        let row = generation.checked_sub(1).ok_or_else(invalid_label)?;
+        if row > MAX_POSITION_ROW || number == 0 || number > 1 << row {
+            return Err(invalid_label());
+        }
+
+        Ok(Position(row, (number - 1) as u8))
+    }
+}
+
+// Columns are stored in a u8, so a tree can't go further than this row
+const MAX_POSITION_ROW: u8 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionParseError {
+    InvalidPath(String),
+    InvalidLabel(String),
+}
+
+impl std::fmt::Display for PositionParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionParseError::InvalidPath(path) => {
+                write!(f, "\"{}\" isn't a path like \"root → female → male\"", path)
+            }
+            PositionParseError::InvalidLabel(label) => {
+                write!(f, "\"{}\" isn't a label like \"Gen 3 #2\"", label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionParseError {}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_label())
+    }
+}
+
+// Reads a position from either a path or a label
+impl std::str::FromStr for Position {
+    type Err = PositionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim_start().starts_with("Gen") {
+            Position::from_label(s)
+        } else {
+            Position::from_path(s)
+        }
+    }
 }
 
 type PokemonBreedTreePositionMap = HashMap<u8, PokemonBreedTreePosition>;
@@ -60,17 +245,305 @@ pub struct PokemonBreedTreeNode {
     pub gender: Option<PokemonGender>,
     pub nature: Option<PokemonNature>,
     pub ivs: Vec<PokemonIv>,
+    // How to evolve the hatched pokemon into this one, only set on the final pokemon when it
+    // isn't the species that hatches from the egg
+    pub evolution_note: Option<String>,
+}
+
+impl PokemonBreedTreeNode {
+    pub fn is_ditto(&self) -> bool {
+        self.pokemon
+            .as_ref()
+            .is_some_and(|pokemon| pokemon.is_ditto())
+    }
 }
 
 type PokemonNodes = HashMap<Position, PokemonBreedTreeNode>;
 
-#[derive(Debug)]
+// Where a node is at while the user works through the plan. Leaves go from planned to acquired
+// when they're bought or caught, the rest go to bred or failed once their parents are bred.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PokemonBreedNodeState {
+    #[default]
+    Planned,
+    Acquired,
+    Bred,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PokemonBreedStep {
+    Acquire(Position),
+    Breed(Position),
+}
+
+// What it takes to recover from a breed that didn't go as planned, compared to the plan
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PokemonBreedReplan {
+    // Whether the pokemon that was bred is still part of the plan
+    pub reused: bool,
+    pub extra_breeders: usize,
+    pub extra_breeds: usize,
+    pub extra_cost: u32,
+}
+
+// What revalidating the nodes around an edit found, compared to the errors from before it
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PokemonBreedRevalidation {
+    pub checked: Vec<Position>,
+    pub new_errors: Vec<Position>,
+    pub fixed_errors: Vec<Position>,
+}
+
+impl PokemonBreedRevalidation {
+    pub fn changed_errors(&self) -> bool {
+        !self.new_errors.is_empty() || !self.fixed_errors.is_empty()
+    }
+}
+
+// A part of the tree taken out of it, with positions relative to its root
+#[derive(Debug, Default)]
+struct PokemonBreedSubtree {
+    pokemon_nodes: PokemonNodes,
+    owned: HashSet<Position>,
+    progress: HashMap<Position, PokemonBreedNodeState>,
+}
+
+// A change made to the tree by one of its methods
+#[derive(Debug, Clone, PartialEq)]
+pub enum PokemonBreedTreeEvent {
+    NodeInserted(Position),
+    // The species, nature or ivs of the node changed
+    NodeChanged(Position),
+    NodeRemoved(Position),
+    GenderAssigned(Position, Option<PokemonGender>),
+    // Positions that got a breeding error, and positions that don't have one anymore
+    DiagnosticsChanged {
+        new_errors: Vec<Position>,
+        fixed_errors: Vec<Position>,
+    },
+    ProgressUpdated(Position, PokemonBreedNodeState),
+}
+
+// What the positions changed by the tree's methods were like before their first change, so
+// events can be made from them alone instead of comparing whole trees
+#[derive(Debug, Clone, Default)]
+struct PokemonBreedTreeJournal {
+    nodes: HashMap<Position, (Option<PokemonBreedTreeNode>, PokemonBreedNodeState)>,
+    breed_errors: Option<Vec<Position>>,
+}
+
+impl PokemonBreedTreeJournal {
+    // Events for every journaled position that is different in the tree now. Node events come
+    // first, sorted by position, then progress, then errors.
+    fn into_events(self, after: Option<&PokemonBreedTree>) -> Vec<PokemonBreedTreeEvent> {
+        let mut positions = self.nodes.keys().copied().collect::<Vec<Position>>();
+        positions.sort();
+        let after_node = |pos: &Position| after.and_then(|tree| tree.pokemon_nodes.get(pos));
+
+        let mut events = vec![];
+        for pos in &positions {
+            let (before, _) = &self.nodes[pos];
+            match (before, after_node(pos)) {
+                (Some(_), None) => events.push(PokemonBreedTreeEvent::NodeRemoved(*pos)),
+                (None, Some(_)) => events.push(PokemonBreedTreeEvent::NodeInserted(*pos)),
+                (Some(before), Some(after)) => {
+                    let changes = node_changes(before, after);
+                    if changes
+                        .iter()
+                        .any(|change| !matches!(change, PokemonBreedFieldChange::Gender(..)))
+                    {
+                        events.push(PokemonBreedTreeEvent::NodeChanged(*pos));
+                    }
+                    if before.gender != after.gender {
+                        events.push(PokemonBreedTreeEvent::GenderAssigned(
+                            *pos,
+                            after.gender.clone(),
+                        ));
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        // A new node that isn't planned anymore has its progress updated too
+        if let Some(after) = after {
+            for pos in positions
+                .iter()
+                .filter(|pos| after.pokemon_nodes.contains_key(pos))
+            {
+                let (_, before_state) = self.nodes[pos];
+                let after_state = after.node_state(*pos);
+                if before_state != after_state {
+                    events.push(PokemonBreedTreeEvent::ProgressUpdated(*pos, after_state));
+                }
+            }
+        }
+
+        if let Some(before_errors) = self.breed_errors {
+            let after_errors = after
+                .map(|tree| tree.breed_errors.as_slice())
+                .unwrap_or_default();
+            let new_errors = after_errors
+                .iter()
+                .filter(|pos| !before_errors.contains(pos))
+                .copied()
+                .collect::<Vec<Position>>();
+            let fixed_errors = before_errors
+                .iter()
+                .filter(|pos| !after_errors.contains(pos))
+                .copied()
+                .collect::<Vec<Position>>();
+            if !new_errors.is_empty() || !fixed_errors.is_empty() {
+                events.push(PokemonBreedTreeEvent::DiagnosticsChanged {
+                    new_errors,
+                    fixed_errors,
+                });
+            }
+        }
+
+        events
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct PokemonBreedTree {
     pub pokemon_nodes: PokemonNodes,
     pub breed_errors: Vec<Position>,
+    // Pokemon the user already owns, they don't need to be bought or bred
+    pub owned: HashSet<Position>,
+    // Nodes without a state are still planned
+    pub progress: HashMap<Position, PokemonBreedNodeState>,
+    // The iv carried by each kind of 1x31 breeder
+    pub ivs_map: FinalPokemonIvsMap,
+    // Changes made by the methods of the tree since the last take_events. Writing to the fields
+    // directly isn't tracked.
+    journal: PokemonBreedTreeJournal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PokemonBreedTreeError {
+    InvalidPosition(Position),
+    // Only pokemon outside the female line can be replaced by a Ditto,
+    // the female line has to carry the species
+    FemaleLineDitto(Position),
+    // The pokemon doesn't have the ivs or nature the node needs
+    MissingRequirements(Position),
+    // The species isn't part of any genderless evolution line
+    NotGenderless(u16),
+    // The pokemon at this position can't be of the requested gender
+    UnavailableGender(Position, PokemonGender),
+    // The species can't produce eggs of its own, even with a Ditto
+    CannotBreed(u16),
+    // The species isn't in the pokedex
+    UnknownSpecies(u16),
+    // Breeding charts only go up to 5 ivs
+    TooManyIvs(usize),
+    // The steps can't be ordered to fit in the storage, at least this many pokemon have to be
+    // kept at once
+    NotEnoughStorage(usize),
+    // Only subtrees with the same number of ivs, where neither is part of the other, can be
+    // swapped
+    InvalidSwap(Position, Position),
+    // The pokemon at this position is bred, it doesn't use a breeder kind
+    NotALeaf(Position),
+    // The tree has no iv for this kind of breeder
+    UnmappedBreederKind(PokemonBreederKind),
+    // An upgrade needs a seed with at least one iv or a nature to keep
+    EmptySeed,
+    // The nodes planned at this position would go past the last row of a tree
+    TooDeep(Position),
+    // There is no market price for a pokemon with the ivs of the one at this position
+    NotForSale(Position),
+    // The priority at this index is already listed before it
+    DuplicatePriority(usize),
+    // The edit would take an iv or the nature away from the final pokemon
+    ChangesFinalPokemon,
+}
+
+impl std::fmt::Display for PokemonBreedTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PokemonBreedTreeError::InvalidPosition(pos) => {
+                write!(f, "There is no pokemon at {}", pos)
+            }
+            PokemonBreedTreeError::FemaleLineDitto(pos) => write!(
+                f,
+                "{} is on the female line and has to carry the species, it can't be a Ditto",
+                pos
+            ),
+            PokemonBreedTreeError::MissingRequirements(pos) => write!(
+                f,
+                "The pokemon doesn't have the ivs or nature needed at {}",
+                pos
+            ),
+            PokemonBreedTreeError::NotGenderless(number) => write!(
+                f,
+                "Pokemon #{} isn't part of a genderless evolution line",
+                number
+            ),
+            PokemonBreedTreeError::UnavailableGender(pos, gender) => write!(
+                f,
+                "The pokemon at {} can't be {:?} because of its species",
+                pos, gender
+            ),
+            PokemonBreedTreeError::CannotBreed(number) => {
+                write!(f, "Pokemon #{} can't be obtained by breeding", number)
+            }
+            PokemonBreedTreeError::UnknownSpecies(number) => {
+                write!(f, "Pokemon #{} isn't in the pokedex", number)
+            }
+            PokemonBreedTreeError::TooManyIvs(count) => write!(
+                f,
+                "A pokemon with {} ivs can't be bred, the most is 5 ivs",
+                count
+            ),
+            PokemonBreedTreeError::NotEnoughStorage(needed) => write!(
+                f,
+                "The plan needs room for at least {} pokemon at once",
+                needed
+            ),
+            PokemonBreedTreeError::InvalidSwap(a, b) => write!(
+                f,
+                "{} and {} can't be swapped, they need the same number of ivs and can't be \
+                 part of each other",
+                a, b
+            ),
+            PokemonBreedTreeError::NotALeaf(pos) => write!(
+                f,
+                "The pokemon at {} is bred, only bought pokemon have a breeder kind",
+                pos
+            ),
+            PokemonBreedTreeError::UnmappedBreederKind(kind) => {
+                write!(f, "The tree has no iv for breeder kind {:?}", kind)
+            }
+            PokemonBreedTreeError::EmptySeed => {
+                write!(f, "The pokemon to upgrade has no iv or nature to keep")
+            }
+            PokemonBreedTreeError::TooDeep(pos) => write!(
+                f,
+                "The pokemon needed for {} would go past the last generation of a tree",
+                pos
+            ),
+            PokemonBreedTreeError::NotForSale(pos) => write!(
+                f,
+                "The pokemon at {} has no market price, it can only be bred",
+                pos
+            ),
+            PokemonBreedTreeError::DuplicatePriority(i) => {
+                write!(f, "Priority #{} is already listed before it", i + 1)
+            }
+            PokemonBreedTreeError::ChangesFinalPokemon => write!(
+                f,
+                "The edit would take an iv or the nature away from the final pokemon"
+            ),
+        }
+    }
 }
 
-type FinalPokemonIvsMap = HashMap<PokemonBreederKind, PokemonIv>;
+impl std::error::Error for PokemonBreedTreeError {}
+
+pub type FinalPokemonIvsMap = HashMap<PokemonBreederKind, PokemonIv>;
 
 impl PokemonBreedTree {
     pub fn new(
@@ -81,168 +554,1620 @@ impl PokemonBreedTree {
             .pokemon
             .clone()
             .expect("This should exist");
-        let mut pokemon_nodes = HashMap::from([(Position(0, 0), final_pokemon_node.clone())]);
-        let last_row_map = init_last_row_mapping();
-        let natured = final_pokemon_node.nature.is_some();
-        let generations = if natured {
-            (final_pokemon_node.ivs.len() + 1) as u8
-        } else {
-            final_pokemon_node.ivs.len() as u8
+
+        let mut tree = PokemonBreedTree {
+            pokemon_nodes: init_tree_nodes(final_pokemon_node, final_pokemon_ivs_map),
+            breed_errors: vec![],
+            owned: HashSet::new(),
+            progress: HashMap::new(),
+            ivs_map: final_pokemon_ivs_map.clone(),
+            journal: PokemonBreedTreeJournal::default(),
         };
+        tree.assign_genders();
 
-        let last_row_breeders = last_row_map.get(&generations).expect("This shouldn't happen. Tried to access last_row_map with an invalid generations number");
+        tree
+    }
 
-        init_pokemon_nodes(
-            generations,
-            &mut pokemon_nodes,
-            if natured {
-                &last_row_breeders.natured
-            } else {
-                &last_row_breeders.natureless
-            },
-            final_pokemon_node,
-            final_pokemon_ivs_map,
-        );
+    // Plans the tree with the breeding pattern the final pokemon species allows. Genderless and
+    // male only species need a Ditto on every pair to pass down the species, every other species
+    // follows the usual pattern where the female line carries it.
+    pub fn plan(
+        final_pokemon_node: &PokemonBreedTreeNode,
+        final_pokemon_ivs_map: &FinalPokemonIvsMap,
+    ) -> Result<PokemonBreedTree, PokemonBreedTreeError> {
+        let final_pokemon = final_pokemon_node
+            .pokemon
+            .clone()
+            .expect("This should exist");
+        // Pokemon that can't breed can still be hatched from an egg of their evolution line
+        let breedable = final_pokemon.can_breed() || is_in_evolution_line(final_pokemon.number);
+        if final_pokemon.is_ditto() || !breedable {
+            return Err(PokemonBreedTreeError::CannotBreed(final_pokemon.number));
+        }
+        // Only genderless species can be requested without a gender, any other species hatches
+        // as a male or a female
+        if let Some(ref gender) = final_pokemon_node.gender {
+            let unavailable = match gender {
+                PokemonGender::Genderless => !final_pokemon.is_genderless(),
+                _ => final_pokemon.gender_chance(gender) <= 0.0,
+            };
+            if unavailable {
+                return Err(PokemonBreedTreeError::UnavailableGender(
+                    Position(0, 0),
+                    gender.clone(),
+                ));
+            }
+        }
 
-        PokemonBreedTree {
-            pokemon_nodes,
-            breed_errors: vec![],
+        if final_pokemon.is_genderless() {
+            return PokemonBreedTree::new_genderless(final_pokemon_node, final_pokemon_ivs_map);
+        }
+
+        let mut tree = PokemonBreedTree::new(final_pokemon_node, final_pokemon_ivs_map);
+        if final_pokemon.is_male_only() {
+            tree.pair_female_line_with_dittos()?;
         }
+
+        Ok(tree)
     }
 
-    pub fn get_final_pokemon_node(&self) -> &PokemonBreedTreeNode {
-        self.pokemon_nodes
-            .get(&Position(0, 0))
+    // Genderless pokemon can only breed with a Ditto, so every pair is made of the genderless
+    // pokemon and a Ditto carrying the rest of the ivs. This leaves only the female line and
+    // their Ditto partners in the tree.
+    pub fn new_genderless(
+        final_pokemon_node: &PokemonBreedTreeNode,
+        final_pokemon_ivs_map: &FinalPokemonIvsMap,
+    ) -> Result<PokemonBreedTree, PokemonBreedTreeError> {
+        let final_pokemon = final_pokemon_node
+            .pokemon
+            .clone()
+            .expect("This should exist");
+        if !GENDERLESS_POKEMON_EVOLUTION_TREE.contains_key(&final_pokemon.number) {
+            return Err(PokemonBreedTreeError::NotGenderless(final_pokemon.number));
+        }
+        match final_pokemon_node.gender {
+            Some(PokemonGender::Genderless) | None => {}
+            Some(ref gender) => {
+                return Err(PokemonBreedTreeError::UnavailableGender(
+                    Position(0, 0),
+                    gender.clone(),
+                ))
+            }
+        }
+
+        let mut tree = PokemonBreedTree::new(final_pokemon_node, final_pokemon_ivs_map);
+        tree.pair_female_line_with_dittos()?;
+        tree.pokemon_nodes
+            .get_mut(&Position(0, 0))
             .expect("This should exist")
-    }
+            .gender = Some(PokemonGender::Genderless);
 
-    pub fn insert_pokemon(&mut self, position: Position, pokemon_node: PokemonBreedTreeNode) {
-        self.pokemon_nodes.insert(position, pokemon_node);
+        Ok(tree)
     }
-}
 
-// Initialize the pokemon nodes based on the initial position_map and the final pokemon ivs & nature.
-fn init_pokemon_nodes(
-    generations: u8,
-    pokemon_nodes: &mut PokemonNodes,
-    last_row_breeders: &PokemonBreederKindPositions,
-    final_pokemon_node: &PokemonBreedTreeNode,
-    final_pokemon_ivs: &FinalPokemonIvsMap,
-) {
-    // initialize last row
-    for (k, v) in last_row_breeders {
-        match v {
-            PokemonBreederKind::Nature => {
-                pokemon_nodes.insert(
-                    *k,
-                    PokemonBreedTreeNode {
-                        pokemon: None,
-                        gender: None,
-                        ivs: vec![],
-                        nature: final_pokemon_node.nature,
-                    },
-                );
-            }
-            _ => {
-                pokemon_nodes.insert(
-                    *k,
-                    PokemonBreedTreeNode {
-                        pokemon: None,
-                        gender: None,
-                        ivs: vec![final_pokemon_ivs.get(v).expect("Should exist").clone()],
-                        nature: None,
-                    },
-                );
+    // Plans the breeds that add the missing ivs to a pokemon the user already has, keeping its
+    // nature. The seed is bred once per missing iv with a partner that shares its ivs and brings
+    // the new one, so only the partners are bred from 1x31 pokemon.
+    // A natured seed holds an everstone and can't pass an iv of its own, so its partners need
+    // one more iv than a natureless seed's partners.
+    // A male seed can't pass down its species, so its first partner is a female of the species,
+    // bred like the female line of its own subtree.
+    pub fn new_upgrade(
+        seed: &PokemonBreedTreeNode,
+        target_ivs: &[PokemonIv],
+    ) -> Result<PokemonBreedTree, PokemonBreedTreeError> {
+        let seed_pokemon = seed.pokemon.clone().expect("This should exist");
+        if seed.ivs.is_empty() && seed.nature.is_none() {
+            return Err(PokemonBreedTreeError::EmptySeed);
+        }
+        let needs_dittos = seed_pokemon.is_genderless() || seed_pokemon.is_male_only();
+        let partner_carries_species = seed.gender == Some(PokemonGender::Male) && !needs_dittos;
+        let mut ivs = seed.ivs.clone();
+        // An iv given more than once is still added by a single breed
+        let mut missing_ivs: Vec<PokemonIv> = vec![];
+        for iv in target_ivs {
+            if !seed.ivs.contains(iv) && !missing_ivs.contains(iv) {
+                missing_ivs.push(iv.clone());
             }
-        };
-    }
+        }
+        let generations = missing_ivs.len() as u8;
 
-    // initialize the rest of the tree
-    // start from the second to last row
-    // stops on the first row where the final pokemon node is already set
-    let mut row = generations - 2;
-    while row > 0 {
-        let mut col = 0;
-        while col < (2 as u8).pow(row as u32) {
-            let pos = Position(row, col);
-            let (p1_pos, p2_pos) = pos.get_parent_positions();
+        let mut tree = PokemonBreedTree {
+            pokemon_nodes: HashMap::from([(Position(generations, 0), seed.clone())]),
+            breed_errors: vec![],
+            owned: HashSet::from([Position(generations, 0)]),
+            progress: HashMap::new(),
+            ivs_map: HashMap::new(),
+            journal: PokemonBreedTreeJournal::default(),
+        };
 
-            let invalid_positions = p1_pos.0 > generations || p2_pos.0 > generations;
-            if invalid_positions {
-                continue;
+        let mut row = generations;
+        for missing_iv in missing_ivs {
+            let mut partner_ivs = ivs.clone();
+            if seed.nature.is_none() {
+                // The seed line passes this one with a power item
+                partner_ivs.remove(0);
+            }
+            partner_ivs.push(missing_iv.clone());
+            if partner_ivs.len() > 5 {
+                return Err(PokemonBreedTreeError::TooManyIvs(partner_ivs.len()));
             }
-            let p1_node = pokemon_nodes.get(&p1_pos).expect("Should exist");
-            let p2_node = pokemon_nodes.get(&p2_pos).expect("Should exist");
-
-            let mut ivs: Vec<PokemonIv> = vec![];
-            ivs.extend(p1_node.ivs.clone());
-            ivs.extend(p2_node.ivs.clone());
 
-            let mut nature: Option<PokemonNature> = None;
-            if p1_node.nature.is_some() {
-                nature = p1_node.nature.clone();
-            } else if p2_node.nature.is_some() {
-                nature = p2_node.nature.clone();
+            let partner_node = PokemonBreedTreeNode {
+                pokemon: None,
+                gender: None,
+                nature: None,
+                ivs: partner_ivs.clone(),
+                evolution_note: None,
+            };
+            let mut partner_nodes = init_tree_nodes(&partner_node, &ivs_map(&partner_ivs));
+            if partner_carries_species && row == generations {
+                for (pos, node) in partner_nodes.iter_mut() {
+                    if pos.is_female_line() {
+                        node.pokemon = Some(seed_pokemon.clone());
+                    }
+                }
             }
+            tree.graft_subtree(Position(row, 1), partner_nodes)?;
 
-            pokemon_nodes.insert(
-                pos,
+            ivs.push(missing_iv);
+            row -= 1;
+            tree.pokemon_nodes.insert(
+                Position(row, 0),
                 PokemonBreedTreeNode {
                     pokemon: None,
                     gender: None,
-                    nature,
-                    ivs,
+                    nature: seed.nature,
+                    ivs: ivs.clone(),
+                    evolution_note: None,
                 },
             );
+        }
 
-            col = col + 1;
+        tree.pokemon_nodes
+            .get_mut(&Position(0, 0))
+            .expect("This should exist")
+            .pokemon = Some(seed_pokemon.clone());
+        tree.ivs_map = ivs_map(&ivs);
+        tree.assign_genders();
+        if needs_dittos {
+            tree.pair_female_line_with_dittos()?;
         }
 
-        row = row - 1;
+        Ok(tree)
     }
-}
 
-// This type represents what the last row of pokemon iv's should be, depending on the nr of
-// generations
-type LastRowMapping = HashMap<u8, PokemonBreedTreePosition>;
+    // Eggs hatch as the base form of the final pokemon, so the female line is filled with the
+    // first form of its evolution line that can breed. Female line pokemon of another species
+    // are left for validation to report.
+    pub fn fill_hatch_species(&mut self, pokedex: &Pokedex) -> Result<(), PokemonBreedTreeError> {
+        let final_pokemon = self
+            .get_final_pokemon_node()
+            .pokemon
+            .clone()
+            .expect("This should exist");
+        let hatch_species =
+            pokedex
+                .hatch_species(&final_pokemon)
+                .ok_or(PokemonBreedTreeError::UnknownSpecies(base_form_number(
+                    final_pokemon.number,
+                )))?;
+        let breedable_species = pokedex
+            .breedable_form(&final_pokemon)
+            .ok_or(PokemonBreedTreeError::CannotBreed(hatch_species.number))?;
 
-fn init_last_row_mapping() -> LastRowMapping {
-    let last_row = HashMap::<u8, PokemonBreedTreePosition>::from([
-        (
-            2,
-            PokemonBreedTreePosition {
-                natured: HashMap::from([
-                    (Position(2, 0), PokemonBreederKind::Nature),
-                    (Position(2, 1), PokemonBreederKind::A),
-                    (Position(2, 2), PokemonBreederKind::A),
-                    (Position(2, 3), PokemonBreederKind::B),
-                ]),
-                natureless: HashMap::from([
-                    (Position(1, 0), PokemonBreederKind::A),
-                    (Position(1, 1), PokemonBreederKind::B),
-                ]),
-            },
-        ),
-        (
-            3,
-            PokemonBreedTreePosition {
-                natured: HashMap::from([
-                    (Position(3, 0), PokemonBreederKind::Nature),
-                    (Position(3, 1), PokemonBreederKind::A),
-                    (Position(3, 2), PokemonBreederKind::A),
-                    (Position(3, 3), PokemonBreederKind::B),
-                    (Position(3, 4), PokemonBreederKind::A),
-                    (Position(3, 5), PokemonBreederKind::B),
-                    (Position(3, 6), PokemonBreederKind::A),
-                    (Position(3, 7), PokemonBreederKind::B),
-                ]),
-                natureless: HashMap::from([
-                    (Position(2, 0), PokemonBreederKind::A),
-                    (Position(2, 1), PokemonBreederKind::B),
-                    (Position(2, 2), PokemonBreederKind::A),
-                    (Position(2, 3), PokemonBreederKind::C),
-                ]),
-            },
+        let female_line = self
+            .pokemon_nodes
+            .iter()
+            .filter(|(pos, node)| {
+                let same_family = node
+                    .pokemon
+                    .as_ref()
+                    .is_none_or(|pokemon| pokemon.is_same_family(&final_pokemon));
+                pos.is_female_line() && !pos.is_root() && same_family
+            })
+            .map(|(pos, _)| *pos)
+            .collect::<Vec<Position>>();
+        for pos in female_line {
+            self.touch(pos);
+            self.pokemon_nodes
+                .get_mut(&pos)
+                .expect("Should exist")
+                .pokemon = Some(breedable_species.clone());
+        }
+
+        self.touch(Position(0, 0));
+        self.pokemon_nodes
+            .get_mut(&Position(0, 0))
+            .expect("This should exist")
+            .evolution_note = pokedex.evolution_note(&final_pokemon);
+        self.assign_genders();
+
+        Ok(())
+    }
+
+    // Number of rows of the tree, the final pokemon being the first one
+    pub fn generations(&self) -> u8 {
+        self.pokemon_nodes
+            .keys()
+            .map(|pos| pos.0 + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    // The row of the node, None when there is no node at this position
+    pub fn depth(&self, position: Position) -> Option<u8> {
+        self.pokemon_nodes.get(&position).map(|_| position.0)
+    }
+
+    // The pokemon bred from this one and its partner
+    pub fn child(&self, position: Position) -> Option<Position> {
+        self.depth(position)?;
+
+        position
+            .get_child_position()
+            .filter(|child_pos| self.pokemon_nodes.contains_key(child_pos))
+    }
+
+    pub fn partner(&self, position: Position) -> Option<Position> {
+        if position.is_root() || self.depth(position).is_none() {
+            return None;
+        }
+
+        Some(position.get_partner_position()).filter(|pos| self.pokemon_nodes.contains_key(pos))
+    }
+
+    // The parents bred to obtain this pokemon, None for pokemon that are bought or owned
+    pub fn parents(&self, position: Position) -> Option<(Position, Position)> {
+        if self.depth(position).is_none() || !self.is_bred(position) {
+            return None;
+        }
+
+        Some(position.get_parent_positions())
+    }
+
+    // The positions of a row, from the first column
+    pub fn row(&self, row: u8) -> impl Iterator<Item = Position> + '_ {
+        let mut positions = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| pos.0 == row)
+            .copied()
+            .collect::<Vec<Position>>();
+        positions.sort();
+
+        positions.into_iter()
+    }
+
+    // Every row from the final pokemon to the furthest one
+    pub fn rows(&self) -> impl Iterator<Item = Vec<Position>> + '_ {
+        (0..self.generations()).map(|row| self.row(row).collect())
+    }
+
+    // Pokemon that are bought or owned instead of bred, by row and column
+    pub fn leaves(&self) -> impl Iterator<Item = Position> + '_ {
+        let mut positions = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| !self.is_bred(**pos))
+            .copied()
+            .collect::<Vec<Position>>();
+        positions.sort();
+
+        positions.into_iter()
+    }
+
+    // Every breed of the tree as (child, parent, parent), by row and column of the child
+    pub fn pairs(&self) -> impl Iterator<Item = (Position, Position, Position)> + '_ {
+        let mut positions = self
+            .pokemon_nodes
+            .keys()
+            .filter_map(|pos| {
+                let (p1_pos, p2_pos) = self.parents(*pos)?;
+                Some((*pos, p1_pos, p2_pos))
+            })
+            .collect::<Vec<(Position, Position, Position)>>();
+        positions.sort();
+
+        positions.into_iter()
+    }
+
+    // The position and every node bred to obtain it, by row and column. Empty when there is
+    // no node at the position.
+    pub fn subtree(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        let mut positions = self.subtree_positions(position);
+        positions.sort();
+
+        positions.into_iter()
+    }
+
+    pub fn get_final_pokemon_node(&self) -> &PokemonBreedTreeNode {
+        self.pokemon_nodes
+            .get(&Position(0, 0))
+            .expect("This should exist")
+    }
+
+    // The kind of breeder of a pokemon with a single iv or only the nature, None for any other
+    pub fn breeder_kind(&self, position: Position) -> Option<PokemonBreederKind> {
+        let node = self.pokemon_nodes.get(&position)?;
+
+        match (node.ivs.as_slice(), node.nature) {
+            ([], Some(_)) => Some(PokemonBreederKind::Nature),
+            ([iv], None) => self
+                .ivs_map
+                .iter()
+                .find(|(_, kind_iv)| *kind_iv == iv)
+                .map(|(kind, _)| kind.clone()),
+            _ => None,
+        }
+    }
+
+    // The species a node is bred as. Nodes of the female line without a pokemon yet are
+    // expected to carry the species of the final pokemon.
+    pub fn species_at(&self, position: Position) -> Option<&Pokemon> {
+        let node = self.pokemon_nodes.get(&position)?;
+
+        match node.pokemon {
+            Some(ref pokemon) => Some(pokemon),
+            None if position.is_female_line() => self.get_final_pokemon_node().pokemon.as_ref(),
+            None => None,
+        }
+    }
+
+    // Inserts the pokemon and checks again only the pairs it's part of, so breed_errors stays
+    // as a full validation would leave it, as long as it was up to date before the insert
+    pub fn insert_pokemon(
+        &mut self,
+        position: Position,
+        pokemon_node: PokemonBreedTreeNode,
+    ) -> PokemonBreedRevalidation {
+        self.touch(position);
+        self.pokemon_nodes.insert(position, pokemon_node);
+        self.revalidate(position)
+    }
+
+    // The pokemon at this position is bought instead of bred, so nothing above it is needed.
+    // Only pokemon with a market price for their ivs can be bought.
+    pub fn replace_with_purchase(
+        &mut self,
+        position: Position,
+        costs: &PokemonBreedCosts,
+    ) -> Result<(), PokemonBreedTreeError> {
+        let node = self
+            .pokemon_nodes
+            .get(&position)
+            .ok_or(PokemonBreedTreeError::InvalidPosition(position))?;
+        if costs.buy_price(node).is_none() {
+            return Err(PokemonBreedTreeError::NotForSale(position));
+        }
+
+        self.prune_parents(position);
+        self.revalidate(position);
+
+        Ok(())
+    }
+
+    // Inserts a pokemon the user already has. Nothing above it needs to be bred anymore, so
+    // those nodes are removed from the tree.
+    pub fn insert_owned_pokemon(
+        &mut self,
+        position: Position,
+        pokemon_node: PokemonBreedTreeNode,
+    ) -> PokemonBreedRevalidation {
+        self.prune_parents(position);
+        self.owned.insert(position);
+        self.insert_pokemon(position, pokemon_node)
+    }
+
+    // Whether the pokemon can take the place of the node at this position. It needs the ivs,
+    // nature and gender of the node, and it has to be able to breed with the partner into the
+    // species of the child.
+    pub fn accepts_pokemon(&self, position: Position, candidate: &PokemonBreedTreeNode) -> bool {
+        let Some(node) = self.pokemon_nodes.get(&position) else {
+            return false;
+        };
+
+        let has_ivs = node.ivs.iter().all(|iv| candidate.ivs.contains(iv));
+        let has_nature = node.nature.is_none() || node.nature == candidate.nature;
+        if !has_ivs || !has_nature {
+            return false;
+        }
+
+        if candidate.is_ditto() {
+            if position.is_female_line() {
+                return false;
+            }
+        } else if node.gender.is_some() && node.gender != candidate.gender {
+            return false;
+        }
+
+        if position.is_female_line() && !position.is_root() {
+            let final_pokemon = self.get_final_pokemon_node().pokemon.as_ref();
+            let same_family = match (&candidate.pokemon, final_pokemon) {
+                (Some(pokemon), Some(final_pokemon)) => pokemon.is_same_family(final_pokemon),
+                _ => true,
+            };
+            if !same_family {
+                return false;
+            }
+        }
+
+        let partner_pos = position.get_partner_position();
+        let Some(partner) = self.pokemon_nodes.get(&partner_pos) else {
+            return true;
+        };
+        let child_species = position
+            .get_child_position()
+            .and_then(|child_pos| self.species_at(child_pos));
+        let passes_species = match (child_species, species_carrier(candidate, partner)) {
+            (Some(child), Some(Some(carrier))) => carrier.is_same_family(child),
+            _ => true,
+        };
+
+        passes_species && can_breed_together(candidate, partner)
+    }
+
+    // Number of breeds needed to complete the tree
+    pub fn breed_count(&self) -> usize {
+        self.pokemon_nodes
+            .keys()
+            .filter(|pos| self.is_bred(**pos))
+            .count()
+    }
+
+    // Whether the pokemon at this position is obtained by breeding its parents, instead of
+    // being bought or owned
+    pub fn is_bred(&self, position: Position) -> bool {
+        // Nodes on the last row a tree can have don't have parents
+        if !self.pokemon_nodes.contains_key(&position) || position.0 >= MAX_POSITION_ROW {
+            return false;
+        }
+
+        let (p1_pos, p2_pos) = position.get_parent_positions();
+
+        self.pokemon_nodes.contains_key(&p1_pos) && self.pokemon_nodes.contains_key(&p2_pos)
+    }
+
+    pub fn node_state(&self, position: Position) -> PokemonBreedNodeState {
+        self.progress.get(&position).copied().unwrap_or_default()
+    }
+
+    pub fn set_node_state(
+        &mut self,
+        position: Position,
+        state: PokemonBreedNodeState,
+    ) -> Result<(), PokemonBreedTreeError> {
+        if !self.pokemon_nodes.contains_key(&position) {
+            return Err(PokemonBreedTreeError::InvalidPosition(position));
+        }
+
+        // Both parents are used up by a breed, even a failed one, so everything bred to obtain
+        // them is planned again. Owned pokemon that were used up are bred again from 1x31s.
+        if state == PokemonBreedNodeState::Failed {
+            for pos in self.subtree_positions(position) {
+                if pos == position || !self.pokemon_nodes.contains_key(&pos) {
+                    continue;
+                }
+
+                self.touch(pos);
+                self.progress.remove(&pos);
+                if self.owned.contains(&pos) {
+                    let node = self.pokemon_nodes[&pos].clone();
+                    self.rebuild_subtree(pos, &node)?;
+                }
+            }
+        }
+        self.touch(position);
+        self.progress.insert(position, state);
+
+        Ok(())
+    }
+
+    // Whether the pokemon at this position is already in the user's hands, or isn't needed
+    // anymore because a pokemon bred from it is
+    pub fn is_done(&self, position: Position) -> bool {
+        let mut pos = Some(position);
+        while let Some(current) = pos {
+            let done = self.owned.contains(&current)
+                || matches!(
+                    self.node_state(current),
+                    PokemonBreedNodeState::Acquired | PokemonBreedNodeState::Bred
+                );
+            if done {
+                return true;
+            }
+
+            pos = current.get_child_position();
+        }
+
+        false
+    }
+
+    // Steps that can be done right now: leaves that still have to be acquired, and breeds whose
+    // parents are both done
+    pub fn next_steps(&self) -> Vec<PokemonBreedStep> {
+        let mut steps = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| !self.is_done(**pos))
+            .filter_map(|pos| {
+                if !self.is_bred(*pos) {
+                    return Some(PokemonBreedStep::Acquire(*pos));
+                }
+
+                let (p1_pos, p2_pos) = pos.get_parent_positions();
+                if self.is_done(p1_pos) && self.is_done(p2_pos) {
+                    Some(PokemonBreedStep::Breed(*pos))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<PokemonBreedStep>>();
+        steps.sort();
+
+        steps
+    }
+
+    // Every step left to complete the tree, in an order that can be followed: first every
+    // pokemon to acquire, then the breeds from the furthest row down to the final pokemon
+    pub fn step_order(&self) -> Vec<PokemonBreedStep> {
+        let mut steps = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| !self.is_done(**pos))
+            .map(|pos| {
+                if self.is_bred(*pos) {
+                    PokemonBreedStep::Breed(*pos)
+                } else {
+                    PokemonBreedStep::Acquire(*pos)
+                }
+            })
+            .collect::<Vec<PokemonBreedStep>>();
+        steps.sort_by_key(|step| match step {
+            PokemonBreedStep::Acquire(pos) => (0, 0, *pos),
+            PokemonBreedStep::Breed(pos) => (1, u8::MAX - pos.0, *pos),
+        });
+
+        steps
+    }
+
+    // Orders the steps left so that no more than storage_limit pokemon are kept at once, counting
+    // the ones already acquired or bred that wait for their breed. Every pair is done by first
+    // completing the parent whose subtree needs the most room, like registers are allocated
+    // when evaluating an expression tree.
+    pub fn schedule_steps(
+        &self,
+        storage_limit: usize,
+    ) -> Result<Vec<PokemonBreedStep>, PokemonBreedTreeError> {
+        // Breeds that can be done right now only free room, so they go first
+        let mut tree = self.clone();
+        let mut steps = vec![];
+        loop {
+            let ready_breeds = tree
+                .next_steps()
+                .into_iter()
+                .filter(|step| matches!(step, PokemonBreedStep::Breed(_)))
+                .collect::<Vec<PokemonBreedStep>>();
+            if ready_breeds.is_empty() {
+                break;
+            }
+
+            for step in ready_breeds {
+                if let PokemonBreedStep::Breed(pos) = step {
+                    tree.progress.insert(pos, PokemonBreedNodeState::Bred);
+                }
+                steps.push(step);
+            }
+        }
+
+        let mut storage = HashMap::new();
+        let (needed, _) = tree.storage_needed(Position(0, 0), &mut storage);
+        if needed > storage_limit {
+            return Err(PokemonBreedTreeError::NotEnoughStorage(needed));
+        }
+        tree.push_scheduled_steps(Position(0, 0), &storage, &mut steps);
+
+        Ok(steps)
+    }
+
+    // Returns the most pokemon kept at once to obtain the one at this position, and how many
+    // pokemon of its subtree are kept right now. The order of the parents that needs the least
+    // room is stored with it, true when the first parent goes first.
+    fn storage_needed(
+        &self,
+        position: Position,
+        storage: &mut HashMap<Position, (usize, usize, bool)>,
+    ) -> (usize, usize) {
+        let needed = if self.is_done(position) {
+            (1, 1, true)
+        } else if !self.is_bred(position) {
+            (1, 0, true)
+        } else {
+            let (p1_pos, p2_pos) = position.get_parent_positions();
+            let (p1_needed, p1_kept) = self.storage_needed(p1_pos, storage);
+            let (p2_needed, p2_kept) = self.storage_needed(p2_pos, storage);
+
+            // While a parent is completed, the pokemon of the other subtree are kept, and the
+            // completed parent is kept while the other one is completed
+            let p1_first = (p1_needed + p2_kept).max(1 + p2_needed);
+            let p2_first = (p2_needed + p1_kept).max(1 + p1_needed);
+            if p1_first <= p2_first {
+                (p1_first, p1_kept + p2_kept, true)
+            } else {
+                (p2_first, p1_kept + p2_kept, false)
+            }
+        };
+        storage.insert(position, needed);
+
+        (needed.0, needed.1)
+    }
+
+    fn push_scheduled_steps(
+        &self,
+        position: Position,
+        storage: &HashMap<Position, (usize, usize, bool)>,
+        steps: &mut Vec<PokemonBreedStep>,
+    ) {
+        if self.is_done(position) {
+            return;
+        }
+        if !self.is_bred(position) {
+            steps.push(PokemonBreedStep::Acquire(position));
+            return;
+        }
+
+        let (p1_pos, p2_pos) = position.get_parent_positions();
+        let (_, _, p1_first) = storage[&position];
+        let (first, second) = if p1_first {
+            (p1_pos, p2_pos)
+        } else {
+            (p2_pos, p1_pos)
+        };
+        self.push_scheduled_steps(first, storage, steps);
+        self.push_scheduled_steps(second, storage, steps);
+        steps.push(PokemonBreedStep::Breed(position));
+    }
+
+    // Percentage of the pokemon to acquire or breed that are done. Owned pokemon don't count.
+    pub fn completion_percentage(&self) -> f32 {
+        let steps = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| !self.owned.contains(pos))
+            .collect::<Vec<&Position>>();
+        if steps.is_empty() {
+            return 100.0;
+        }
+
+        let done = steps.iter().filter(|pos| self.is_done(***pos)).count();
+
+        done as f32 * 100.0 / steps.len() as f32
+    }
+
+    // Replaces the node with a Ditto that has the given ivs and nature.
+    // A Ditto can't be bred, so every node above it is removed from the tree.
+    pub fn insert_ditto(
+        &mut self,
+        position: Position,
+        ivs: Vec<PokemonIv>,
+        nature: Option<PokemonNature>,
+    ) -> Result<(), PokemonBreedTreeError> {
+        let node = self
+            .pokemon_nodes
+            .get(&position)
+            .ok_or(PokemonBreedTreeError::InvalidPosition(position))?;
+
+        if position.is_female_line() {
+            return Err(PokemonBreedTreeError::FemaleLineDitto(position));
+        }
+
+        let has_ivs = node.ivs.iter().all(|iv| ivs.contains(iv));
+        let has_nature = node.nature.is_none() || node.nature == nature;
+        if !has_ivs || !has_nature {
+            return Err(PokemonBreedTreeError::MissingRequirements(position));
+        }
+
+        self.prune_parents(position);
+        self.touch(position);
+        self.pokemon_nodes.insert(
+            position,
+            PokemonBreedTreeNode {
+                pokemon: Some(Pokemon::ditto()),
+                gender: Some(PokemonGender::Genderless),
+                nature,
+                ivs,
+                evolution_note: None,
+            },
+        );
+        self.revalidate(position);
+
+        Ok(())
+    }
+
+    // Takes the pokemon that was actually obtained at this position when it doesn't match the
+    // plan, like a wrong gender or a breed that missed an iv, and plans again the nodes that
+    // lead to it. The pokemon is kept when it still fits, as it is, in the place of its partner
+    // when only the gender is wrong, or as the seed of an upgrade when it misses ivs.
+    pub fn replan(
+        &mut self,
+        position: Position,
+        actual: PokemonBreedTreeNode,
+        costs: &PokemonBreedCosts,
+    ) -> Result<PokemonBreedReplan, PokemonBreedTreeError> {
+        let node = self
+            .pokemon_nodes
+            .get(&position)
+            .cloned()
+            .ok_or(PokemonBreedTreeError::InvalidPosition(position))?;
+
+        // What would be left to do if the breed went as planned
+        let mut planned = self.clone();
+        planned.mark_subtree_done(position);
+
+        let reused = self.reuse_pokemon(position, &node, &actual);
+        if !reused {
+            self.rebuild_subtree(position, &node)?;
+        }
+        self.validate();
+
+        let pending = |tree: &PokemonBreedTree, bred: bool| {
+            tree.pokemon_nodes
+                .keys()
+                .filter(|pos| !tree.is_done(**pos) && tree.is_bred(**pos) == bred)
+                .count()
+        };
+
+        Ok(PokemonBreedReplan {
+            reused,
+            extra_breeders: pending(self, false).saturating_sub(pending(&planned, false)),
+            extra_breeds: pending(self, true).saturating_sub(pending(&planned, true)),
+            extra_cost: costs
+                .remaining_cost(self)
+                .total()
+                .saturating_sub(costs.remaining_cost(&planned).total()),
+        })
+    }
+
+    fn reuse_pokemon(
+        &mut self,
+        position: Position,
+        node: &PokemonBreedTreeNode,
+        actual: &PokemonBreedTreeNode,
+    ) -> bool {
+        if self.accepts_pokemon(position, actual) {
+            self.insert_pokemon(position, actual.clone());
+            self.mark_subtree_done(position);
+            return true;
+        }
+
+        let has_ivs = node.ivs.iter().all(|iv| actual.ivs.contains(iv));
+        let has_nature = node.nature.is_none() || node.nature == actual.nature;
+
+        // Only the gender is wrong. The pokemon can take the place of its partner, as long as
+        // every node of the partner is still planned. Owned pokemon stay where they are and
+        // bred or failed ones already used up their parents.
+        if has_ivs && has_nature && !position.is_root() {
+            let partner_pos = position.get_partner_position();
+            let partner_planned = self.subtree_positions(partner_pos).iter().all(|pos| {
+                !self.owned.contains(pos) && self.node_state(*pos) == PokemonBreedNodeState::Planned
+            });
+
+            if partner_planned {
+                let mut swapped = self.clone();
+                swapped.exchange_subtrees(position, partner_pos);
+                swapped.assign_genders();
+
+                if swapped.accepts_pokemon(partner_pos, actual) {
+                    *self = swapped;
+                    self.insert_pokemon(partner_pos, actual.clone());
+                    self.mark_subtree_done(partner_pos);
+                    return true;
+                }
+            }
+        }
+
+        // Some ivs are missing. The pokemon can be bred again to add them, instead of breeding
+        // the node from 1x31 pokemon.
+        let partial_ivs =
+            !actual.ivs.is_empty() && actual.ivs.iter().all(|iv| node.ivs.contains(iv));
+        if has_nature && !has_ivs && partial_ivs && !actual.is_ditto() {
+            let seed = PokemonBreedTreeNode {
+                pokemon: actual
+                    .pokemon
+                    .clone()
+                    .or_else(|| self.species_at(position).cloned()),
+                nature: node.nature,
+                ..actual.clone()
+            };
+
+            let upgrade = seed
+                .pokemon
+                .is_some()
+                .then(|| PokemonBreedTree::new_upgrade(&seed, &node.ivs));
+            let fits = |upgrade: &PokemonBreedTree| {
+                upgrade
+                    .pokemon_nodes
+                    .keys()
+                    .all(|pos| pos.moved_under(position).is_some())
+            };
+            if let Some(Ok(upgrade)) = upgrade.filter(|upgrade| upgrade.as_ref().is_ok_and(fits)) {
+                let moved = |pos: Position| pos.moved_under(position).expect("Should fit");
+                self.prune_parents(position);
+                self.touch(position);
+                self.progress.remove(&position);
+                for (pos, upgrade_node) in upgrade.pokemon_nodes {
+                    if !pos.is_root() {
+                        self.touch(moved(pos));
+                        self.pokemon_nodes.insert(moved(pos), upgrade_node);
+                    }
+                }
+                for pos in upgrade.owned {
+                    self.touch(moved(pos));
+                    self.progress
+                        .insert(moved(pos), PokemonBreedNodeState::Bred);
+                }
+
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // Plans again every node needed to obtain this one, starting from 1x31 pokemon
+    fn rebuild_subtree(
+        &mut self,
+        position: Position,
+        node: &PokemonBreedTreeNode,
+    ) -> Result<(), PokemonBreedTreeError> {
+        // Dittos are bought, not bred
+        let mut subtree_nodes = if node.is_ditto() {
+            HashMap::from([(Position(0, 0), node.clone())])
+        } else {
+            init_tree_nodes(node, &ivs_map(&node.ivs))
+        };
+        for (pos, subtree_node) in subtree_nodes.iter_mut() {
+            // The female line of the subtree carries its species
+            if pos.is_female_line() && !position.is_root() {
+                subtree_node.pokemon = node.pokemon.clone();
+            }
+        }
+
+        self.prune_parents(position);
+        self.touch(position);
+        self.owned.remove(&position);
+        self.progress.remove(&position);
+        self.graft_subtree(position, subtree_nodes)?;
+        if node.is_ditto() {
+            return Ok(());
+        }
+
+        let final_pokemon = self.get_final_pokemon_node().pokemon.clone();
+        let needs_dittos =
+            final_pokemon.is_some_and(|pokemon| pokemon.is_genderless() || pokemon.is_male_only());
+        if position.is_female_line() && needs_dittos {
+            // Every pokemon on the female line was already paired with a Ditto, so this can't fail
+            self.pair_female_line_with_dittos()
+                .expect("Should be a valid tree");
+        } else {
+            self.assign_genders();
+        }
+
+        Ok(())
+    }
+
+    // Swaps the two parents of the pokemon at this position, along with everything bred to
+    // obtain them. Parents take the gender of their new column.
+    pub fn swap_parents(&mut self, position: Position) -> Result<(), PokemonBreedTreeError> {
+        let (p1_pos, p2_pos) = self
+            .parents(position)
+            .ok_or(PokemonBreedTreeError::InvalidPosition(position))?;
+
+        let mut swapped = self.clone();
+        swapped.exchange_subtrees(p1_pos, p2_pos);
+        swapped.assign_genders();
+        swapped.recalculate_children(position);
+        swapped.keeps_final_pokemon(self)?;
+        // Owned pokemon keep their gender wherever they end up, so their new pairs are checked
+        swapped.revalidate(p1_pos);
+        swapped.revalidate(p2_pos);
+        *self = swapped;
+
+        Ok(())
+    }
+
+    // Swaps two subtrees that need the same number of ivs, like moving a cheaper iv to the
+    // bigger subtree. The pokemon bred from them get their ivs and nature again.
+    pub fn swap_subtrees(&mut self, a: Position, b: Position) -> Result<(), PokemonBreedTreeError> {
+        let a_node = self
+            .pokemon_nodes
+            .get(&a)
+            .ok_or(PokemonBreedTreeError::InvalidPosition(a))?;
+        let b_node = self
+            .pokemon_nodes
+            .get(&b)
+            .ok_or(PokemonBreedTreeError::InvalidPosition(b))?;
+
+        let overlapping = a.is_in_subtree_of(b) || b.is_in_subtree_of(a);
+        if overlapping || a_node.ivs.len() != b_node.ivs.len() {
+            return Err(PokemonBreedTreeError::InvalidSwap(a, b));
+        }
+
+        let mut swapped = self.clone();
+        swapped.exchange_subtrees(a, b);
+        swapped.assign_genders();
+        for pos in [a, b] {
+            if let Some(child_pos) = swapped.child(pos) {
+                swapped.recalculate_children(child_pos);
+            }
+        }
+        swapped.keeps_final_pokemon(self)?;
+        swapped.revalidate(a);
+        swapped.revalidate(b);
+        *self = swapped;
+
+        Ok(())
+    }
+
+    // Changes the iv or nature a bought pokemon carries to the one of another kind of breeder.
+    // The final pokemon has to keep every iv and its nature.
+    pub fn set_breeder_kind(
+        &mut self,
+        position: Position,
+        kind: PokemonBreederKind,
+    ) -> Result<(), PokemonBreedTreeError> {
+        if !self.pokemon_nodes.contains_key(&position) {
+            return Err(PokemonBreedTreeError::InvalidPosition(position));
+        }
+        if self.is_bred(position) {
+            return Err(PokemonBreedTreeError::NotALeaf(position));
+        }
+
+        let (ivs, nature) = match kind {
+            PokemonBreederKind::Nature => (vec![], self.get_final_pokemon_node().nature),
+            _ => (
+                vec![self
+                    .ivs_map
+                    .get(&kind)
+                    .ok_or(PokemonBreedTreeError::UnmappedBreederKind(kind.clone()))?
+                    .clone()],
+                None,
+            ),
+        };
+        let mut edited = self.clone();
+        let node = edited
+            .pokemon_nodes
+            .get_mut(&position)
+            .expect("Should exist");
+        node.ivs = ivs;
+        node.nature = nature;
+
+        if let Some(child_pos) = edited.child(position) {
+            edited.recalculate_children(child_pos);
+        }
+        edited.keeps_final_pokemon(self)?;
+        edited.revalidate(position);
+        *self = edited;
+
+        Ok(())
+    }
+
+    // Edits can move ivs and natures around, but never take one away from the final pokemon
+    fn keeps_final_pokemon(&self, before: &PokemonBreedTree) -> Result<(), PokemonBreedTreeError> {
+        let node = self.get_final_pokemon_node();
+        let before_node = before.get_final_pokemon_node();
+        if same_ivs(&node.ivs, &before_node.ivs) && node.nature == before_node.nature {
+            Ok(())
+        } else {
+            Err(PokemonBreedTreeError::ChangesFinalPokemon)
+        }
+    }
+
+    // Sets the ivs and nature of the pokemon at this position, and of every pokemon bred from
+    // it down to the final pokemon, from what their parents pass down
+    fn recalculate_children(&mut self, position: Position) {
+        let mut position = Some(position);
+
+        while let Some(pos) = position {
+            if let Some((p1_pos, p2_pos)) = self.parents(pos) {
+                let p1_node = &self.pokemon_nodes[&p1_pos];
+                let p2_node = &self.pokemon_nodes[&p2_pos];
+
+                let mut ivs: Vec<PokemonIv> = vec![];
+                for iv in p1_node.ivs.iter().chain(p2_node.ivs.iter()) {
+                    if !ivs.contains(iv) {
+                        ivs.push(iv.clone());
+                    }
+                }
+                let nature = p1_node.nature.or(p2_node.nature);
+
+                self.touch(pos);
+                let node = self.pokemon_nodes.get_mut(&pos).expect("Should exist");
+                node.ivs = ivs;
+                node.nature = nature;
+            }
+
+            position = self.child(pos);
+        }
+    }
+
+    // Every pair needs a female and a male. Females are on the even columns and carry the
+    // species, except for Dittos and genderless pokemon, which pair with each other, and gender
+    // locked species, which always get their only gender. Owned pokemon keep their gender and
+    // their partners get the opposite one.
+    // The final pokemon isn't part of a pair, so it keeps whatever gender was requested for it.
+    pub fn assign_genders(&mut self) {
+        let owned_genders = self
+            .owned
+            .iter()
+            .filter_map(|pos| Some((*pos, self.pokemon_nodes.get(pos)?.gender.clone()?)))
+            .collect::<HashMap<Position, PokemonGender>>();
+
+        let mut genders = vec![];
+        for (pos, node) in &self.pokemon_nodes {
+            if pos.is_root() || self.owned.contains(pos) {
+                continue;
+            }
+
+            let partner_gender = owned_genders.get(&pos.get_partner_position());
+            let gender = match node.pokemon {
+                Some(ref pokemon) if pokemon.is_ditto() || pokemon.is_genderless() => {
+                    PokemonGender::Genderless
+                }
+                Some(ref pokemon) if pokemon.is_male_only() => PokemonGender::Male,
+                Some(ref pokemon) if pokemon.is_female_only() => PokemonGender::Female,
+                _ if partner_gender == Some(&PokemonGender::Female) => PokemonGender::Male,
+                _ if partner_gender == Some(&PokemonGender::Male) => PokemonGender::Female,
+                _ if pos.1 % 2 == 0 => PokemonGender::Female,
+                _ => PokemonGender::Male,
+            };
+            if node.gender.as_ref() != Some(&gender) {
+                genders.push((*pos, gender));
+            }
+        }
+        for (pos, gender) in genders {
+            self.touch(pos);
+            self.pokemon_nodes
+                .get_mut(&pos)
+                .expect("Should exist")
+                .gender = Some(gender);
+        }
+        // Any pair can have a new gender, so every pair is checked again
+        self.validate();
+    }
+
+    // Checks every breeding pair of the tree and stores the positions of the parents that can't
+    // breed together, or that can't pass down the species of their child, in breed_errors.
+    // Pokemon with a gender their species can't have are stored there too.
+    pub fn validate(&mut self) {
+        let mut breed_errors = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| self.has_breed_error(**pos))
+            .copied()
+            .collect::<Vec<Position>>();
+
+        breed_errors.sort();
+        breed_errors.dedup();
+        self.touch_errors();
+        self.breed_errors = breed_errors;
+    }
+
+    // Validates again the pokemon whose errors can depend on the node at this position: the
+    // node and its partner, its parents, since it's the child they have to pass the species
+    // to, and the nodes on the path to the root with their partners.
+    // Errors of nodes that aren't in the tree anymore are dropped too.
+    fn revalidate(&mut self, position: Position) -> PokemonBreedRevalidation {
+        let mut checked = vec![];
+        let (p1_pos, p2_pos) = position.get_parent_positions();
+        checked.extend([p1_pos, p2_pos]);
+        let mut pos = Some(position);
+        while let Some(current) = pos {
+            checked.push(current);
+            if !current.is_root() {
+                checked.push(current.get_partner_position());
+            }
+            pos = current.get_child_position();
+        }
+        checked.retain(|pos| self.pokemon_nodes.contains_key(pos));
+        checked.sort();
+
+        let mut revalidation = PokemonBreedRevalidation::default();
+        self.touch_errors();
+        let pokemon_nodes = &self.pokemon_nodes;
+        self.breed_errors.retain(|pos| {
+            let exists = pokemon_nodes.contains_key(pos);
+            if !exists {
+                revalidation.fixed_errors.push(*pos);
+            }
+            exists
+        });
+
+        for pos in &checked {
+            let had_error = self.breed_errors.contains(pos);
+            match (had_error, self.has_breed_error(*pos)) {
+                (false, true) => {
+                    self.breed_errors.push(*pos);
+                    revalidation.new_errors.push(*pos);
+                }
+                (true, false) => {
+                    self.breed_errors.retain(|error_pos| error_pos != pos);
+                    revalidation.fixed_errors.push(*pos);
+                }
+                _ => {}
+            }
+        }
+
+        self.breed_errors.sort();
+        revalidation.fixed_errors.sort();
+        revalidation.checked = checked;
+        revalidation
+    }
+
+    // The changes made by the methods of the tree since the last call, for every position that
+    // ended up different
+    pub fn take_events(&mut self) -> Vec<PokemonBreedTreeEvent> {
+        std::mem::take(&mut self.journal).into_events(Some(self))
+    }
+
+    // The changes that turn one tree into the other, for a tree that replaces another one as a
+    // whole. A missing tree has no nodes.
+    pub fn replacement_events(
+        before: Option<&PokemonBreedTree>,
+        after: Option<&PokemonBreedTree>,
+    ) -> Vec<PokemonBreedTreeEvent> {
+        let mut journal = PokemonBreedTreeJournal {
+            breed_errors: Some(
+                before
+                    .map(|tree| tree.breed_errors.clone())
+                    .unwrap_or_default(),
+            ),
+            ..Default::default()
+        };
+        for tree in before.iter().chain(after.iter()) {
+            for pos in tree.pokemon_nodes.keys() {
+                let before_node = before.and_then(|tree| tree.pokemon_nodes.get(pos)).cloned();
+                let before_state = before.map(|tree| tree.node_state(*pos)).unwrap_or_default();
+                journal.nodes.insert(*pos, (before_node, before_state));
+            }
+        }
+
+        journal.into_events(after)
+    }
+
+    // Keeps what the position was like before its first change since the last take_events
+    fn touch(&mut self, position: Position) {
+        if !self.journal.nodes.contains_key(&position) {
+            let before = (
+                self.pokemon_nodes.get(&position).cloned(),
+                self.node_state(position),
+            );
+            self.journal.nodes.insert(position, before);
+        }
+    }
+
+    fn touch_errors(&mut self) {
+        if self.journal.breed_errors.is_none() {
+            self.journal.breed_errors = Some(self.breed_errors.clone());
+        }
+    }
+
+    // Whether the pokemon has a gender its species can't have, or can't breed with its partner
+    // into the species of their child
+    fn has_breed_error(&self, position: Position) -> bool {
+        let Some(node) = self.pokemon_nodes.get(&position) else {
+            return false;
+        };
+
+        if let (Some(gender), Some(pokemon)) = (&node.gender, &node.pokemon) {
+            if pokemon.gender_chance(gender) <= 0.0 {
+                return true;
+            }
+        }
+
+        let Some(child) = position
+            .get_child_position()
+            .and_then(|child_pos| self.pokemon_nodes.get(&child_pos))
+        else {
+            return false;
+        };
+        let Some(partner) = self.pokemon_nodes.get(&position.get_partner_position()) else {
+            return false;
+        };
+
+        let Position(_, col) = position;
+        let (p1_node, p2_node) = if col % 2 == 0 {
+            (node, partner)
+        } else {
+            (partner, node)
+        };
+
+        let passes_species = match (&child.pokemon, species_carrier(p1_node, p2_node)) {
+            (Some(child), Some(Some(carrier))) => carrier.is_same_family(child),
+            _ => true,
+        };
+
+        !can_breed_together(p1_node, p2_node) || !passes_species
+    }
+
+    // Replaces the partner of every female line pokemon with a Ditto, for species that can't
+    // pass down their species through a female
+    fn pair_female_line_with_dittos(&mut self) -> Result<(), PokemonBreedTreeError> {
+        let final_pokemon = self
+            .get_final_pokemon_node()
+            .pokemon
+            .clone()
+            .expect("This should exist");
+
+        let mut row = 1;
+        while let Some(node) = self.pokemon_nodes.get(&Position(row, 1)) {
+            self.insert_ditto(Position(row, 1), node.ivs.clone(), node.nature)?;
+            row += 1;
+        }
+
+        let female_line = self
+            .pokemon_nodes
+            .keys()
+            .filter(|pos| pos.is_female_line() && !pos.is_root())
+            .copied()
+            .collect::<Vec<Position>>();
+        for pos in female_line {
+            self.touch(pos);
+            self.pokemon_nodes
+                .get_mut(&pos)
+                .expect("Should exist")
+                .pokemon = Some(final_pokemon.clone());
+        }
+        self.assign_genders();
+
+        Ok(())
+    }
+
+    // Inserts the nodes of a tree rooted at (0, 0) so that its root ends up at this position
+    fn graft_subtree(
+        &mut self,
+        position: Position,
+        pokemon_nodes: PokemonNodes,
+    ) -> Result<(), PokemonBreedTreeError> {
+        let moved_nodes = pokemon_nodes
+            .into_iter()
+            .map(|(pos, node)| Some((pos.moved_under(position)?, node)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(PokemonBreedTreeError::TooDeep(position))?;
+        for (pos, _) in &moved_nodes {
+            self.touch(*pos);
+        }
+        self.pokemon_nodes.extend(moved_nodes);
+
+        Ok(())
+    }
+
+    // Removes every node that would have been bred to obtain the pokemon at this position
+    fn prune_parents(&mut self, position: Position) {
+        let (p1_pos, p2_pos) = position.get_parent_positions();
+
+        for parent_pos in [p1_pos, p2_pos] {
+            self.touch(parent_pos);
+            if self.pokemon_nodes.remove(&parent_pos).is_some() {
+                self.owned.remove(&parent_pos);
+                self.progress.remove(&parent_pos);
+                self.prune_parents(parent_pos);
+            }
+        }
+    }
+
+    // The position and every node above it
+    fn subtree_positions(&self, position: Position) -> Vec<Position> {
+        if !self.pokemon_nodes.contains_key(&position) {
+            return vec![];
+        }
+
+        let (p1_pos, p2_pos) = position.get_parent_positions();
+        let mut positions = vec![position];
+        positions.extend(self.subtree_positions(p1_pos));
+        positions.extend(self.subtree_positions(p2_pos));
+
+        positions
+    }
+
+    fn take_subtree(&mut self, position: Position) -> PokemonBreedSubtree {
+        let mut subtree = PokemonBreedSubtree::default();
+
+        for pos in self.subtree_positions(position) {
+            let relative_pos = pos.relative_to(position);
+            self.touch(pos);
+            let node = self.pokemon_nodes.remove(&pos).expect("Should exist");
+            subtree.pokemon_nodes.insert(relative_pos, node);
+            if self.owned.remove(&pos) {
+                subtree.owned.insert(relative_pos);
+            }
+            if let Some(state) = self.progress.remove(&pos) {
+                subtree.progress.insert(relative_pos, state);
+            }
+        }
+
+        subtree
+    }
+
+    // Subtrees are only put where one of the same depth was taken, so they always fit
+    fn put_subtree(&mut self, position: Position, subtree: PokemonBreedSubtree) {
+        let moved = |pos: Position| pos.moved_under(position).expect("Should fit");
+        self.graft_subtree(position, subtree.pokemon_nodes)
+            .expect("Should fit");
+        self.owned.extend(subtree.owned.into_iter().map(moved));
+        for (pos, state) in subtree.progress {
+            self.touch(moved(pos));
+            self.progress.insert(moved(pos), state);
+        }
+    }
+
+    fn exchange_subtrees(&mut self, a: Position, b: Position) {
+        let subtree_a = self.take_subtree(a);
+        let subtree_b = self.take_subtree(b);
+        self.put_subtree(b, subtree_a);
+        self.put_subtree(a, subtree_b);
+    }
+
+    // Marks the position and everything that was used to obtain it as done
+    fn mark_subtree_done(&mut self, position: Position) {
+        for pos in self.subtree_positions(position) {
+            if self.owned.contains(&pos) {
+                continue;
+            }
+
+            let state = if self.is_bred(pos) {
+                PokemonBreedNodeState::Bred
+            } else {
+                PokemonBreedNodeState::Acquired
+            };
+            self.touch(pos);
+            self.progress.insert(pos, state);
+        }
+    }
+}
+
+// The parent whose species the egg hatches as: the partner of a Ditto, or else the female
+fn species_carrier<'a>(
+    p1: &'a PokemonBreedTreeNode,
+    p2: &'a PokemonBreedTreeNode,
+) -> Option<&'a Option<Pokemon>> {
+    if p1.is_ditto() {
+        return Some(&p2.pokemon);
+    }
+    if p2.is_ditto() {
+        return Some(&p1.pokemon);
+    }
+
+    match (&p1.gender, &p2.gender) {
+        (Some(PokemonGender::Female), _) => Some(&p1.pokemon),
+        (_, Some(PokemonGender::Female)) => Some(&p2.pokemon),
+        _ => None,
+    }
+}
+
+fn can_breed_together(p1: &PokemonBreedTreeNode, p2: &PokemonBreedTreeNode) -> bool {
+    // A Ditto breeds with anything that can breed, except another Ditto
+    match (p1.is_ditto(), p2.is_ditto()) {
+        (true, true) => return false,
+        (true, false) => return p2.pokemon.as_ref().is_none_or(|p| p.can_breed()),
+        (false, true) => return p1.pokemon.as_ref().is_none_or(|p| p.can_breed()),
+        (false, false) => {}
+    }
+
+    if let (Some(g1), Some(g2)) = (&p1.gender, &p2.gender) {
+        let opposite_genders = matches!(
+            (g1, g2),
+            (PokemonGender::Female, PokemonGender::Male)
+                | (PokemonGender::Male, PokemonGender::Female)
+        );
+        if !opposite_genders {
+            return false;
+        }
+    }
+
+    // Genderless pokemon can only breed with a Ditto
+    let genderless = [p1, p2].iter().any(|node| {
+        node.pokemon
+            .as_ref()
+            .is_some_and(|pokemon| pokemon.is_genderless())
+    });
+    if genderless {
+        return false;
+    }
+
+    match (&p1.pokemon, &p2.pokemon) {
+        (Some(pokemon1), Some(pokemon2)) => {
+            pokemon1.can_breed() && pokemon2.can_breed() && pokemon1.shares_egg_group(pokemon2)
+        }
+        _ => true,
+    }
+}
+
+// Maps the ivs to the breeder kinds in order, the first iv is carried by the A breeders
+pub fn ivs_map(ivs: &[PokemonIv]) -> FinalPokemonIvsMap {
+    [
+        PokemonBreederKind::A,
+        PokemonBreederKind::B,
+        PokemonBreederKind::C,
+        PokemonBreederKind::D,
+        PokemonBreederKind::E,
+    ]
+    .into_iter()
+    .zip(ivs.iter().cloned())
+    .collect()
+}
+
+fn init_tree_nodes(
+    final_pokemon_node: &PokemonBreedTreeNode,
+    final_pokemon_ivs_map: &FinalPokemonIvsMap,
+) -> PokemonNodes {
+    let mut pokemon_nodes = HashMap::from([(Position(0, 0), final_pokemon_node.clone())]);
+    let last_row_map = init_last_row_mapping();
+    let natured = final_pokemon_node.nature.is_some();
+    let ivs_count = final_pokemon_node.ivs.len() as u8;
+    let generations = if natured { ivs_count + 1 } else { ivs_count };
+    // A pokemon with a single iv, or only a nature, is bought instead of bred
+    if generations < 2 {
+        return pokemon_nodes;
+    }
+
+    let last_row_breeders = last_row_map
+        .get(&ivs_count)
+        .expect("This shouldn't happen. Tried to access last_row_map with an invalid ivs number");
+
+    init_pokemon_nodes(
+        generations,
+        &mut pokemon_nodes,
+        if natured {
+            &last_row_breeders.natured
+        } else {
+            &last_row_breeders.natureless
+        },
+        final_pokemon_node,
+        final_pokemon_ivs_map,
+    );
+
+    pokemon_nodes
+}
+
+// Initialize the pokemon nodes based on the initial position_map and the final pokemon ivs & nature.
+fn init_pokemon_nodes(
+    generations: u8,
+    pokemon_nodes: &mut PokemonNodes,
+    last_row_breeders: &PokemonBreederKindPositions,
+    final_pokemon_node: &PokemonBreedTreeNode,
+    final_pokemon_ivs: &FinalPokemonIvsMap,
+) {
+    // initialize last row
+    for (k, v) in last_row_breeders {
+        match v {
+            PokemonBreederKind::Nature => {
+                pokemon_nodes.insert(
+                    *k,
+                    PokemonBreedTreeNode {
+                        pokemon: None,
+                        gender: None,
+                        ivs: vec![],
+                        nature: final_pokemon_node.nature,
+                        evolution_note: None,
+                    },
+                );
+            }
+            _ => {
+                pokemon_nodes.insert(
+                    *k,
+                    PokemonBreedTreeNode {
+                        pokemon: None,
+                        gender: None,
+                        ivs: vec![final_pokemon_ivs.get(v).expect("Should exist").clone()],
+                        nature: None,
+                        evolution_note: None,
+                    },
+                );
+            }
+        };
+    }
+
+    // initialize the rest of the tree
+    // start from the second to last row
+    // stops on the first row where the final pokemon node is already set
+    let mut row = generations - 2;
+    while row > 0 {
+        let mut col = 0;
+        while col < 2_u8.pow(row as u32) {
+            let pos = Position(row, col);
+            let (p1_pos, p2_pos) = pos.get_parent_positions();
+
+            let invalid_positions = p1_pos.0 > generations || p2_pos.0 > generations;
+            if invalid_positions {
+                continue;
+            }
+            let p1_node = pokemon_nodes.get(&p1_pos).expect("Should exist");
+            let p2_node = pokemon_nodes.get(&p2_pos).expect("Should exist");
+
+            let mut ivs: Vec<PokemonIv> = vec![];
+            for iv in p1_node.ivs.iter().chain(p2_node.ivs.iter()) {
+                if !ivs.contains(iv) {
+                    ivs.push(iv.clone());
+                }
+            }
+
+            let mut nature: Option<PokemonNature> = None;
+            if p1_node.nature.is_some() {
+                nature = p1_node.nature;
+            } else if p2_node.nature.is_some() {
+                nature = p2_node.nature;
+            }
+
+            pokemon_nodes.insert(
+                pos,
+                PokemonBreedTreeNode {
+                    pokemon: None,
+                    gender: None,
+                    nature,
+                    ivs,
+                    evolution_note: None,
+                },
+            );
+
+            col += 1;
+        }
+
+        row -= 1;
+    }
+}
+
+// This type represents what the last row of pokemon iv's should be, depending on the nr of
+// ivs of the final pokemon
+type LastRowMapping = HashMap<u8, PokemonBreedTreePosition>;
+
+fn init_last_row_mapping() -> LastRowMapping {
+    HashMap::<u8, PokemonBreedTreePosition>::from([
+        (
+            1,
+            PokemonBreedTreePosition {
+                natured: HashMap::from([
+                    (Position(1, 0), PokemonBreederKind::Nature),
+                    (Position(1, 1), PokemonBreederKind::A),
+                ]),
+                natureless: HashMap::new(),
+            },
+        ),
+        (
+            2,
+            PokemonBreedTreePosition {
+                natured: HashMap::from([
+                    (Position(2, 0), PokemonBreederKind::Nature),
+                    (Position(2, 1), PokemonBreederKind::A),
+                    (Position(2, 2), PokemonBreederKind::A),
+                    (Position(2, 3), PokemonBreederKind::B),
+                ]),
+                natureless: HashMap::from([
+                    (Position(1, 0), PokemonBreederKind::A),
+                    (Position(1, 1), PokemonBreederKind::B),
+                ]),
+            },
+        ),
+        (
+            3,
+            PokemonBreedTreePosition {
+                natured: HashMap::from([
+                    (Position(3, 0), PokemonBreederKind::Nature),
+                    (Position(3, 1), PokemonBreederKind::A),
+                    (Position(3, 2), PokemonBreederKind::A),
+                    (Position(3, 3), PokemonBreederKind::B),
+                    (Position(3, 4), PokemonBreederKind::A),
+                    (Position(3, 5), PokemonBreederKind::B),
+                    (Position(3, 6), PokemonBreederKind::A),
+                    (Position(3, 7), PokemonBreederKind::C),
+                ]),
+                natureless: HashMap::from([
+                    (Position(2, 0), PokemonBreederKind::A),
+                    (Position(2, 1), PokemonBreederKind::B),
+                    (Position(2, 2), PokemonBreederKind::A),
+                    (Position(2, 3), PokemonBreederKind::C),
+                ]),
+            },
         ),
         (
             4,
@@ -334,128 +2259,1186 @@ fn init_last_row_mapping() -> LastRowMapping {
                 ]),
             },
         ),
-    ]);
-    last_row
+    ])
 }
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        cost::PokemonBreedCosts,
+        fixtures::{charizard, charmander, final_node},
+        pokedex::Pokedex,
+        pokemon::{Pokemon, PokemonEggGroup, PokemonGender, PokemonIv, PokemonNature, PokemonType},
+        tree::Position,
+    };
+
+    use super::{
+        PokemonBreedNodeState, PokemonBreedStep, PokemonBreedTree, PokemonBreedTreeError,
+        PokemonBreedTreeEvent, PokemonBreedTreeNode, PokemonBreederKind,
+    };
+
+    #[test]
+    fn pokemon_breed_tree_constructor_2iv_test() {
+        let final_ivs = [PokemonIv::Attack, PokemonIv::Speed].to_vec();
+        let final_pokemon_node = final_node(charizard(), &final_ivs, None);
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        assert_eq!(tree.pokemon_nodes.len(), 3);
+        assert_eq!(tree.get_final_pokemon_node(), &final_pokemon_node);
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(1, 0))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Attack]
+        );
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(1, 1))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Speed]
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_constructor_natured_test() {
+        let final_ivs = [PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP].to_vec();
+        let final_pokemon_node = final_node(charizard(), &final_ivs, Some(PokemonNature::Jolly));
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        // The chart is picked by the number of ivs, a natured tree has one more generation
+        assert_eq!(tree.pokemon_nodes.len(), 15);
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(3, 7))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::HP]
+        );
+        // Ivs both parents have are only passed down once
+        for node in tree.pokemon_nodes.values() {
+            assert!(node
+                .ivs
+                .iter()
+                .all(|iv| node.ivs.iter().filter(|other| *other == iv).count() == 1));
+        }
+        let final_node = tree.get_final_pokemon_node();
+        assert_eq!(final_node.ivs.len(), 3);
+        assert_eq!(final_node.nature, Some(PokemonNature::Jolly));
+    }
+
+    #[test]
+    fn pokemon_breed_tree_constructor_3iv_test() {
+        let final_ivs = [PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP].to_vec();
+        let final_pokemon_node = final_node(charizard(), &final_ivs, None);
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        eprintln!("{:#?}", tree);
+
+        assert_eq!(tree.pokemon_nodes.len(), 7);
+        assert_eq!(tree.get_final_pokemon_node(), &final_pokemon_node);
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(2, 0))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Attack]
+        );
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(2, 1))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Speed]
+        );
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(2, 2))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Attack]
+        );
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(2, 3))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::HP]
+        );
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(1, 0))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Attack, PokemonIv::Speed]
+        );
+        assert_eq!(
+            tree.pokemon_nodes
+                .get(&Position(1, 1))
+                .expect("Should exist")
+                .ivs,
+            vec![PokemonIv::Attack, PokemonIv::HP]
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_ditto_test() {
+        let final_ivs = [PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP].to_vec();
+        let final_pokemon_node = final_node(charizard(), &final_ivs, None);
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        assert_eq!(
+            tree.insert_ditto(
+                Position(1, 0),
+                vec![PokemonIv::Attack, PokemonIv::Speed],
+                None
+            ),
+            Err(PokemonBreedTreeError::FemaleLineDitto(Position(1, 0)))
+        );
+        assert_eq!(
+            tree.insert_ditto(Position(1, 1), vec![PokemonIv::Attack], None),
+            Err(PokemonBreedTreeError::MissingRequirements(Position(1, 1)))
+        );
+
+        tree.insert_ditto(Position(2, 2), vec![PokemonIv::Attack], None)
+            .expect("Should be a valid ditto");
+        tree.insert_ditto(Position(2, 3), vec![PokemonIv::HP], None)
+            .expect("Should be a valid ditto");
+        tree.validate();
+        assert_eq!(tree.breed_errors, vec![Position(2, 2), Position(2, 3)]);
+
+        tree.insert_ditto(Position(1, 1), vec![PokemonIv::Attack, PokemonIv::HP], None)
+            .expect("Should be a valid ditto");
+        tree.validate();
+        assert_eq!(tree.pokemon_nodes.len(), 5);
+        assert!(tree.breed_errors.is_empty());
+    }
+
+    #[test]
+    fn pokemon_breed_tree_genderless_test() {
+        let metagross = Pokemon {
+            number: 376,
+            name: "Metagross".to_string(),
+            types: (PokemonType::Steel, Some(PokemonType::Psychic)),
+            egg_groups: (PokemonEggGroup::Genderless, None),
+            percentage_male: 0.0,
+        };
+        let final_pokemon_node = final_node(
+            metagross.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new_genderless(&final_pokemon_node, &final_iv_map)
+            .expect("Metagross is genderless");
+        tree.validate();
+
+        assert_eq!(tree.pokemon_nodes.len(), 5);
+        assert!(tree.breed_errors.is_empty());
+        assert!(tree.pokemon_nodes[&Position(1, 1)].is_ditto());
+        assert!(tree.pokemon_nodes[&Position(2, 1)].is_ditto());
+        assert_eq!(
+            tree.pokemon_nodes[&Position(1, 1)].ivs,
+            vec![PokemonIv::Attack, PokemonIv::HP]
+        );
+
+        // Any pokemon of the Beldum line can carry the species
+        let mut metang_node = tree.pokemon_nodes[&Position(1, 0)].clone();
+        metang_node.pokemon = Some(Pokemon {
+            number: 375,
+            name: "Metang".to_string(),
+            ..metagross.clone()
+        });
+        tree.insert_pokemon(Position(1, 0), metang_node);
+        tree.validate();
+        assert!(tree.breed_errors.is_empty());
+
+        let mut magnemite_node = tree.pokemon_nodes[&Position(1, 0)].clone();
+        magnemite_node.pokemon = Some(Pokemon {
+            number: 81,
+            name: "Magnemite".to_string(),
+            ..metagross.clone()
+        });
+        tree.insert_pokemon(Position(1, 0), magnemite_node);
+        tree.validate();
+        assert_eq!(
+            tree.breed_errors,
+            vec![
+                Position(1, 0),
+                Position(1, 1),
+                Position(2, 0),
+                Position(2, 1)
+            ]
+        );
+
+        let charizard_node = PokemonBreedTreeNode {
+            pokemon: Some(charizard()),
+            ..final_pokemon_node
+        };
+        assert_eq!(
+            PokemonBreedTree::new_genderless(&charizard_node, &final_iv_map).err(),
+            Some(PokemonBreedTreeError::NotGenderless(6))
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_genderless_family_test() {
+        let genderless = |number: u16, name: &str| Pokemon {
+            number,
+            name: name.to_string(),
+            types: (PokemonType::Normal, None),
+            egg_groups: (PokemonEggGroup::Genderless, None),
+            percentage_male: 0.0,
+        };
+        let porygon = genderless(137, "Porygon");
+        let porygon2 = genderless(233, "Porygon2");
+        let porygon_z = genderless(474, "Porygon-Z");
+        let magnemite = genderless(81, "Magnemite");
+        assert!(porygon.is_same_family(&porygon_z));
+        assert!(porygon2.is_same_family(&porygon));
+        assert!(genderless(601, "Klinklang").is_same_family(&genderless(599, "Klink")));
+        assert!(!magnemite.is_same_family(&porygon));
+
+        let final_pokemon_node =
+            final_node(porygon_z, &[PokemonIv::Attack, PokemonIv::Speed], None);
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+        ]);
+        let mut tree = PokemonBreedTree::plan(&final_pokemon_node, &final_iv_map)
+            .expect("Porygon-Z is genderless");
+
+        for (species, has_errors) in [(porygon, false), (porygon2, false), (magnemite, true)] {
+            let mut node = tree.pokemon_nodes[&Position(1, 0)].clone();
+            node.pokemon = Some(species);
+            tree.insert_pokemon(Position(1, 0), node);
+            tree.validate();
+            assert_eq!(!tree.breed_errors.is_empty(), has_errors);
+        }
+    }
+
+    #[test]
+    fn pokemon_breed_tree_gender_locked_test() {
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let tauros_node = PokemonBreedTreeNode {
+            ivs: [PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP].to_vec(),
+            pokemon: Some(Pokemon {
+                number: 128,
+                name: "Tauros".to_string(),
+                types: (PokemonType::Normal, None),
+                egg_groups: (PokemonEggGroup::Field, None),
+                percentage_male: 100.0,
+            }),
+            gender: None,
+            nature: None,
+            evolution_note: None,
+        };
+        let mut tree =
+            PokemonBreedTree::plan(&tauros_node, &final_iv_map).expect("Tauros can be bred");
+        tree.validate();
+
+        // Tauros has no females, so the species is passed down by males bred with Dittos
+        assert_eq!(tree.pokemon_nodes.len(), 5);
+        assert!(tree.breed_errors.is_empty());
+        assert_eq!(
+            tree.pokemon_nodes[&Position(1, 0)].gender,
+            Some(PokemonGender::Male)
+        );
+        assert!(tree.pokemon_nodes[&Position(1, 1)].is_ditto());
+
+        let female_tauros_node = PokemonBreedTreeNode {
+            gender: Some(PokemonGender::Female),
+            ..tauros_node.clone()
+        };
+        assert_eq!(
+            PokemonBreedTree::plan(&female_tauros_node, &final_iv_map).err(),
+            Some(PokemonBreedTreeError::UnavailableGender(
+                Position(0, 0),
+                PokemonGender::Female
+            ))
+        );
+
+        let miltank_node = PokemonBreedTreeNode {
+            pokemon: Some(Pokemon {
+                number: 241,
+                name: "Miltank".to_string(),
+                types: (PokemonType::Normal, None),
+                egg_groups: (PokemonEggGroup::Field, None),
+                percentage_male: 0.0,
+            }),
+            ..tauros_node.clone()
+        };
+        let mut tree =
+            PokemonBreedTree::plan(&miltank_node, &final_iv_map).expect("Miltank can be bred");
+        assert_eq!(tree.pokemon_nodes.len(), 7);
+
+        // A Miltank can't be the male of a pair
+        let mut male_node = tree.pokemon_nodes[&Position(1, 1)].clone();
+        male_node.pokemon = miltank_node.pokemon.clone();
+        male_node.gender = Some(PokemonGender::Male);
+        tree.insert_pokemon(Position(1, 1), male_node);
+        tree.validate();
+        assert!(tree.breed_errors.contains(&Position(1, 1)));
+    }
+
+    #[test]
+    fn pokemon_breed_tree_hatch_species_test() {
+        let charizard = charizard();
+        let charmander = charmander();
+        let charmeleon = Pokemon {
+            number: 5,
+            name: "Charmeleon".to_string(),
+            ..charmander.clone()
+        };
+        let pokedex = Pokedex::new(vec![charmander.clone(), charmeleon, charizard.clone()]);
+        let final_pokemon_node = final_node(
+            charizard.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::plan(&final_pokemon_node, &final_iv_map)
+            .expect("Charizard can be bred");
+        tree.fill_hatch_species(&pokedex)
+            .expect("Charmander is in the pokedex");
+        tree.validate();
+
+        assert!(tree.breed_errors.is_empty());
+        assert_eq!(tree.get_final_pokemon_node().pokemon, Some(charizard));
+        assert_eq!(
+            tree.pokemon_nodes[&Position(1, 0)].pokemon,
+            Some(charmander.clone())
+        );
+        assert_eq!(
+            tree.pokemon_nodes[&Position(2, 0)].pokemon,
+            Some(charmander)
+        );
+        assert_eq!(tree.pokemon_nodes[&Position(1, 1)].pokemon, None);
+        assert_eq!(
+            tree.get_final_pokemon_node().evolution_note,
+            Some(
+                "Hatches as Charmander. Charmander evolves into Charmeleon (Level 16), \
+                 then Charmeleon evolves into Charizard (Level 36)."
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_upgrade_test() {
+        let garchomp = Pokemon {
+            number: 445,
+            name: "Garchomp".to_string(),
+            types: (PokemonType::Dragon, Some(PokemonType::Ground)),
+            egg_groups: (PokemonEggGroup::Monster, Some(PokemonEggGroup::Dragon)),
+            percentage_male: 50.0,
+        };
+        let seed = PokemonBreedTreeNode {
+            pokemon: Some(garchomp.clone()),
+            gender: Some(PokemonGender::Female),
+            nature: Some(PokemonNature::Jolly),
+            ivs: vec![
+                PokemonIv::HP,
+                PokemonIv::Attack,
+                PokemonIv::Defense,
+                PokemonIv::Speed,
+            ],
+            evolution_note: None,
+        };
+        let target_ivs = [
+            PokemonIv::HP,
+            PokemonIv::Attack,
+            PokemonIv::Defense,
+            PokemonIv::SpecialDefense,
+            PokemonIv::Speed,
+        ];
+        let mut tree = PokemonBreedTree::new_upgrade(&seed, &target_ivs)
+            .expect("A 4x31 can be upgraded to 5x31");
+        tree.validate();
 
-    use crate::{
-        pokemon::{Pokemon, PokemonEggGroup, PokemonIv, PokemonType},
-        tree::Position,
-    };
+        // The natured seed is bred once with a natureless 5x31, which takes 15 breeds
+        assert_eq!(tree.breed_count(), 16);
+        assert_eq!(tree.pokemon_nodes.len(), 33);
+        assert!(tree.breed_errors.is_empty());
+        assert_eq!(tree.pokemon_nodes[&Position(1, 0)], seed);
+        assert!(tree.owned.contains(&Position(1, 0)));
+        assert_eq!(tree.pokemon_nodes[&Position(1, 1)].nature, None);
+        assert_eq!(tree.pokemon_nodes[&Position(1, 1)].ivs.len(), 5);
+
+        let final_pokemon_node = tree.get_final_pokemon_node();
+        assert_eq!(final_pokemon_node.pokemon, Some(garchomp));
+        assert_eq!(final_pokemon_node.nature, Some(PokemonNature::Jolly));
+        assert_eq!(final_pokemon_node.ivs.len(), 5);
 
-    use super::{PokemonBreedTree, PokemonBreedTreeNode, PokemonBreederKind};
+        // A natureless male seed passes one of its ivs, so its partners are females that need one
+        // iv less
+        let male_seed = PokemonBreedTreeNode {
+            gender: Some(PokemonGender::Male),
+            nature: None,
+            ivs: vec![PokemonIv::Attack, PokemonIv::Speed],
+            ..seed
+        };
+        let mut tree = PokemonBreedTree::new_upgrade(&male_seed, &target_ivs)
+            .expect("A 2x31 can be upgraded to 5x31");
+        tree.validate();
+
+        assert!(tree.breed_errors.is_empty());
+        assert_eq!(tree.pokemon_nodes[&Position(3, 0)], male_seed);
+        assert_eq!(
+            tree.pokemon_nodes[&Position(3, 1)].ivs,
+            vec![PokemonIv::Speed, PokemonIv::HP]
+        );
+        assert_eq!(
+            tree.pokemon_nodes[&Position(3, 1)].gender,
+            Some(PokemonGender::Female)
+        );
+        assert_eq!(tree.pokemon_nodes[&Position(1, 1)].ivs.len(), 4);
+    }
 
     #[test]
-    fn pokemon_breed_tree_constructor_2iv_test() {
-        let final_ivs = [PokemonIv::Attack, PokemonIv::Speed].to_vec();
-        let final_pokemon = Pokemon {
-            number: 6,
-            name: "Charizard".to_string(),
-            types: (PokemonType::Fire, Some(PokemonType::Flying)),
+    fn pokemon_breed_tree_upgrade_seed_test() {
+        let garchomp = Pokemon {
+            number: 445,
+            name: "Garchomp".to_string(),
+            types: (PokemonType::Dragon, Some(PokemonType::Ground)),
             egg_groups: (PokemonEggGroup::Monster, Some(PokemonEggGroup::Dragon)),
-            percentage_male: 87.5,
+            percentage_male: 50.0,
         };
-        let final_pokemon_node = PokemonBreedTreeNode {
-            ivs: final_ivs.clone(),
-            pokemon: Some(final_pokemon),
-            gender: None,
+        let empty_seed = PokemonBreedTreeNode {
+            pokemon: Some(garchomp.clone()),
+            gender: Some(PokemonGender::Female),
             nature: None,
+            ivs: vec![],
+            evolution_note: None,
+        };
+        assert_eq!(
+            PokemonBreedTree::new_upgrade(&empty_seed, &[PokemonIv::HP, PokemonIv::Attack]).err(),
+            Some(PokemonBreedTreeError::EmptySeed)
+        );
+
+        // The female partner of a male seed carries the species, and so does the female line of
+        // the subtree it's bred from
+        let male_seed = PokemonBreedTreeNode {
+            gender: Some(PokemonGender::Male),
+            ivs: vec![PokemonIv::Attack, PokemonIv::Speed],
+            ..empty_seed
         };
+        let mut tree = PokemonBreedTree::new_upgrade(
+            &male_seed,
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+        )
+        .expect("A 2x31 can be upgraded to 3x31");
+        tree.validate();
+
+        assert!(tree.breed_errors.is_empty());
+        assert_eq!(tree.pokemon_nodes[&Position(1, 0)], male_seed);
+        let repeated = PokemonBreedTree::new_upgrade(
+            &male_seed,
+            &[PokemonIv::Attack, PokemonIv::HP, PokemonIv::HP],
+        )
+        .expect("A 2x31 can be upgraded to 3x31");
+        assert_eq!(repeated.generations(), tree.generations());
+        assert_eq!(repeated.pokemon_nodes.len(), tree.pokemon_nodes.len());
+        for pos in [Position(1, 1), Position(2, 2)] {
+            assert_eq!(tree.pokemon_nodes[&pos].pokemon, Some(garchomp.clone()));
+            assert_eq!(tree.pokemon_nodes[&pos].gender, Some(PokemonGender::Female));
+        }
+        assert_eq!(tree.pokemon_nodes[&Position(2, 3)].pokemon, None);
+    }
+
+    #[test]
+    fn pokemon_breed_tree_progress_test() {
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
         let final_iv_map = HashMap::from([
             (PokemonBreederKind::A, PokemonIv::Attack),
             (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
         ]);
-        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
 
-        assert_eq!(tree.pokemon_nodes.len(), 3);
-        assert_eq!(tree.get_final_pokemon_node(), &final_pokemon_node);
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(1, 0))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::Attack]
+            tree.next_steps(),
+            vec![
+                PokemonBreedStep::Acquire(Position(2, 0)),
+                PokemonBreedStep::Acquire(Position(2, 1)),
+                PokemonBreedStep::Acquire(Position(2, 2)),
+                PokemonBreedStep::Acquire(Position(2, 3)),
+            ]
         );
+        assert_eq!(tree.completion_percentage(), 0.0);
+
+        for col in 0..4 {
+            tree.set_node_state(Position(2, col), PokemonBreedNodeState::Acquired)
+                .expect("Should exist");
+        }
+        tree.set_node_state(Position(1, 0), PokemonBreedNodeState::Bred)
+            .expect("Should exist");
+        tree.set_node_state(Position(1, 1), PokemonBreedNodeState::Failed)
+            .expect("Should exist");
+
+        // The failed breed used up its parents, they have to be acquired again
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(1, 1))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::Speed]
+            tree.next_steps(),
+            vec![
+                PokemonBreedStep::Acquire(Position(2, 2)),
+                PokemonBreedStep::Acquire(Position(2, 3)),
+            ]
+        );
+        assert_eq!(tree.completion_percentage(), 300.0 / 7.0);
+
+        // Everything bred to obtain a bred pokemon is done with it
+        tree.set_node_state(Position(0, 0), PokemonBreedNodeState::Bred)
+            .expect("Should exist");
+        assert!(tree.is_done(Position(2, 3)));
+        assert!(tree.next_steps().is_empty());
+        assert_eq!(tree.completion_percentage(), 100.0);
+        assert_eq!(
+            tree.set_node_state(Position(3, 0), PokemonBreedNodeState::Bred),
+            Err(PokemonBreedTreeError::InvalidPosition(Position(3, 0)))
         );
     }
 
     #[test]
-    fn pokemon_breed_tree_constructor_3iv_test() {
-        let final_ivs = [PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP].to_vec();
-        let final_pokemon = Pokemon {
-            number: 6,
-            name: "Charizard".to_string(),
-            types: (PokemonType::Fire, Some(PokemonType::Flying)),
-            egg_groups: (PokemonEggGroup::Monster, Some(PokemonEggGroup::Dragon)),
-            percentage_male: 87.5,
+    fn pokemon_breed_tree_replan_test() {
+        let costs = PokemonBreedCosts::default();
+        let charmander = charmander();
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        // The female came out male, so it takes the place of its partner and a female has to
+        // be bred from the other pair instead
+        let mut wrong_gender_tree = tree.clone();
+        let male = PokemonBreedTreeNode {
+            pokemon: Some(charmander.clone()),
+            gender: Some(PokemonGender::Male),
+            nature: None,
+            ivs: vec![PokemonIv::Attack, PokemonIv::Speed],
+            evolution_note: None,
         };
-        let final_pokemon_node = PokemonBreedTreeNode {
-            ivs: final_ivs.clone(),
-            pokemon: Some(final_pokemon),
-            gender: None,
+        let replan = wrong_gender_tree
+            .replan(Position(1, 0), male.clone(), &costs)
+            .expect("Should exist");
+
+        assert!(replan.reused);
+        assert_eq!(replan.extra_breeders, 0);
+        assert_eq!(replan.extra_breeds, 0);
+        assert_eq!(
+            replan.extra_cost,
+            costs.gender_selection.rare - costs.gender_selection.common
+        );
+        assert_eq!(wrong_gender_tree.pokemon_nodes[&Position(1, 1)], male);
+        assert_eq!(
+            wrong_gender_tree.pokemon_nodes[&Position(1, 0)].ivs,
+            vec![PokemonIv::Attack, PokemonIv::HP]
+        );
+        assert_eq!(
+            wrong_gender_tree.node_state(Position(2, 2)),
+            PokemonBreedNodeState::Acquired
+        );
+        assert!(wrong_gender_tree.breed_errors.is_empty());
+
+        // A pokemon already bought for the partner can't be moved, the pair is bred again
+        let mut started_partner_tree = tree.clone();
+        started_partner_tree
+            .set_node_state(Position(2, 3), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        let replan = started_partner_tree
+            .replan(Position(1, 0), male.clone(), &costs)
+            .expect("Should exist");
+
+        assert!(!replan.reused);
+        assert_eq!(
+            started_partner_tree.node_state(Position(2, 3)),
+            PokemonBreedNodeState::Acquired
+        );
+        assert_eq!(
+            started_partner_tree.pokemon_nodes[&Position(1, 1)].ivs,
+            tree.pokemon_nodes[&Position(1, 1)].ivs
+        );
+
+        // The male missed the attack iv, so it's bred once more with a 1x31 attack female
+        let mut missing_iv_tree = tree.clone();
+        let missing_iv = PokemonBreedTreeNode {
+            pokemon: Some(charmander.clone()),
+            gender: Some(PokemonGender::Male),
             nature: None,
+            ivs: vec![PokemonIv::HP],
+            evolution_note: None,
+        };
+        let replan = missing_iv_tree
+            .replan(Position(1, 1), missing_iv.clone(), &costs)
+            .expect("Should exist");
+
+        assert!(replan.reused);
+        assert_eq!(replan.extra_breeders, 1);
+        assert_eq!(replan.extra_breeds, 1);
+        assert_eq!(missing_iv_tree.pokemon_nodes[&Position(2, 2)], missing_iv);
+        assert_eq!(
+            missing_iv_tree.node_state(Position(2, 2)),
+            PokemonBreedNodeState::Bred
+        );
+        assert_eq!(
+            missing_iv_tree.pokemon_nodes[&Position(2, 3)].ivs,
+            vec![PokemonIv::Attack]
+        );
+
+        // A pokemon without any of the ivs can't be used, the pair is bred again
+        let mut useless_tree = tree.clone();
+        let replan = useless_tree
+            .replan(
+                Position(1, 1),
+                PokemonBreedTreeNode {
+                    ivs: vec![PokemonIv::Defense],
+                    ..missing_iv
+                },
+                &costs,
+            )
+            .expect("Should exist");
+
+        assert!(!replan.reused);
+        assert_eq!(replan.extra_breeders, 2);
+        assert_eq!(replan.extra_breeds, 1);
+        assert_eq!(useless_tree.pokemon_nodes.len(), tree.pokemon_nodes.len());
+        assert_eq!(
+            useless_tree.replan(Position(3, 0), male, &costs),
+            Err(PokemonBreedTreeError::InvalidPosition(Position(3, 0)))
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_schedule_steps_test() {
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            Some(PokemonNature::Adamant),
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        let most_kept = |tree: &PokemonBreedTree, steps: &[PokemonBreedStep], kept: usize| {
+            steps
+                .iter()
+                .scan(kept, |kept, step| {
+                    match step {
+                        PokemonBreedStep::Acquire(_) => *kept += 1,
+                        PokemonBreedStep::Breed(_) => *kept -= 1,
+                    }
+                    Some(*kept)
+                })
+                .max()
+                .unwrap_or(kept)
         };
+
+        // Buying all the 8 pokemon first needs room for all of them, but 4 is enough when every
+        // pair is bred as soon as possible
+        assert_eq!(most_kept(&tree, &tree.step_order(), 0), 8);
+        assert_eq!(
+            tree.schedule_steps(3),
+            Err(PokemonBreedTreeError::NotEnoughStorage(4))
+        );
+        let steps = tree.schedule_steps(4).expect("Should fit");
+        assert_eq!(steps.len(), 15);
+        assert_eq!(most_kept(&tree, &steps, 0), 4);
+        assert_eq!(steps[0], PokemonBreedStep::Acquire(Position(3, 0)));
+        assert_eq!(steps[14], PokemonBreedStep::Breed(Position(0, 0)));
+
+        // Pokemon that are already waiting for their breed take room too, so their breeds are
+        // done first to free it
+        for col in [2, 3, 4, 5] {
+            tree.set_node_state(Position(3, col), PokemonBreedNodeState::Acquired)
+                .expect("Should exist");
+        }
+        assert_eq!(
+            tree.schedule_steps(3),
+            Err(PokemonBreedTreeError::NotEnoughStorage(4))
+        );
+        let steps = tree.schedule_steps(4).expect("Should fit");
+        assert_eq!(steps.len(), 11);
+        assert_eq!(steps[0], PokemonBreedStep::Breed(Position(2, 1)));
+        assert_eq!(most_kept(&tree, &steps, 4), 4);
+    }
+
+    #[test]
+    fn pokemon_breed_tree_navigation_test() {
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+
+        assert_eq!(tree.generations(), 3);
+        assert_eq!(tree.depth(Position(2, 3)), Some(2));
+        assert_eq!(tree.depth(Position(3, 0)), None);
+        assert_eq!(tree.child(Position(2, 3)), Some(Position(1, 1)));
+        assert_eq!(tree.child(Position(0, 0)), None);
+        assert_eq!(tree.partner(Position(1, 0)), Some(Position(1, 1)));
+        assert_eq!(tree.partner(Position(0, 0)), None);
+        assert_eq!(
+            tree.parents(Position(1, 1)),
+            Some((Position(2, 2), Position(2, 3)))
+        );
+        assert_eq!(tree.parents(Position(2, 0)), None);
+        assert_eq!(
+            tree.rows().map(|row| row.len()).collect::<Vec<usize>>(),
+            vec![1, 2, 4]
+        );
+        assert_eq!(tree.leaves().count(), 4);
+        assert_eq!(
+            tree.pairs()
+                .collect::<Vec<(Position, Position, Position)>>(),
+            vec![
+                (Position(0, 0), Position(1, 0), Position(1, 1)),
+                (Position(1, 0), Position(2, 0), Position(2, 1)),
+                (Position(1, 1), Position(2, 2), Position(2, 3)),
+            ]
+        );
+        assert_eq!(
+            tree.subtree(Position(1, 1)).collect::<Vec<Position>>(),
+            vec![Position(1, 1), Position(2, 2), Position(2, 3)]
+        );
+        assert_eq!(tree.subtree(Position(3, 0)).count(), 0);
+
+        // Positions out of the tree, or out of any tree, have no relatives
+        assert_eq!(tree.parents(Position(0, 200)), None);
+        assert_eq!(tree.parents(Position(255, 0)), None);
+        assert_eq!(tree.parents(Position(2, 255)), None);
+        assert!(!tree.is_bred(Position(1, 200)));
+        assert!(!tree.is_bred(Position(255, 255)));
+        assert_eq!(tree.child(Position(255, 255)), None);
+        assert_eq!(tree.partner(Position(0, 255)), None);
+        assert_eq!(tree.subtree(Position(200, 200)).count(), 0);
+
+        // Only pokemon with a market price can be bought. Once bought, the nodes above it
+        // aren't part of the tree.
+        let mut costs = PokemonBreedCosts::default();
+        assert_eq!(
+            tree.replace_with_purchase(Position(1, 1), &costs),
+            Err(PokemonBreedTreeError::NotForSale(Position(1, 1)))
+        );
+        assert_eq!(tree.leaves().count(), 4);
+        costs.multi_iv_breeders.insert(2, 30000);
+        tree.replace_with_purchase(Position(1, 1), &costs)
+            .expect("Should be for sale");
+        assert_eq!(tree.parents(Position(1, 1)), None);
+        assert_eq!(tree.child(Position(2, 3)), None);
+        assert_eq!(tree.leaves().count(), 3);
+    }
+
+    #[test]
+    fn position_path_test() {
+        let position = Position(2, 1);
+        assert_eq!(position.to_path(), "root → female → male");
+        assert_eq!(position.to_label(), "Gen 3 #2");
+        assert_eq!(Position(0, 0).to_path(), "root");
+        assert_eq!(Position(3, 6).to_path(), "root → male → male → female");
+
+        assert_eq!(Position::from_path("root → female → male"), Ok(position));
+        assert_eq!(Position::from_path("Root > Female > Male"), Ok(position));
+        assert_eq!("Gen 3 #2".parse::<Position>(), Ok(position));
+        assert_eq!("root -> female -> male".parse::<Position>(), Ok(position));
+        for row in 0..6 {
+            for col in 0..(1 << row) {
+                let position = Position(row, col);
+                assert_eq!(Position::from_path(&position.to_path()), Ok(position));
+                assert_eq!(Position::from_label(&position.to_label()), Ok(position));
+            }
+        }
+
+        assert!(Position::from_path("female → male").is_err());
+        assert!(Position::from_path("root → left").is_err());
+        assert!(Position::from_label("Gen 3 #5").is_err());
+        assert!(Position::from_label("Gen 0 #1").is_err());
+        assert!(Position::from_label("Gen 3").is_err());
+
+        assert_eq!(
+            Position(2, 3).moved_under(Position(5, 31)),
+            Some(Position(7, 127))
+        );
+        assert_eq!(
+            Position(7, 127).relative_to(Position(5, 31)),
+            Position(2, 3)
+        );
+        assert_eq!(Position(3, 0).moved_under(Position(5, 0)), None);
+
+        // Rows past the last one of a tree don't overflow
+        assert_eq!(
+            Position::new(9, 1).to_path(),
+            "root → female → female → female → female → female → female → female → female → male"
+        );
+        assert_eq!(
+            Position::new(255, 0).to_path().matches("female").count(),
+            255
+        );
+        assert!(Position::new(40, 0).is_in_subtree_of(Position(0, 0)));
+        assert!(!Position::new(40, 0).is_in_subtree_of(Position(1, 1)));
+        assert!(!Position::new(0, 0).is_in_subtree_of(Position::new(40, 0)));
+
+        // Errors refer to positions by their label
+        assert_eq!(
+            PokemonBreedTreeError::InvalidSwap(Position(1, 0), Position(2, 3)).to_string(),
+            "Gen 2 #1 and Gen 3 #4 can't be swapped, they need the same number of ivs and can't \
+             be part of each other"
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_edit_test() {
+        let final_pokemon_node = final_node(
+            charizard(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
         let final_iv_map = HashMap::from([
             (PokemonBreederKind::A, PokemonIv::Attack),
             (PokemonBreederKind::B, PokemonIv::Speed),
             (PokemonBreederKind::C, PokemonIv::HP),
         ]);
         let tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
-        eprintln!("{:#?}", tree);
+        let ivs = |tree: &PokemonBreedTree, pos: Position| tree.pokemon_nodes[&pos].ivs.clone();
 
-        assert_eq!(tree.pokemon_nodes.len(), 7);
-        assert_eq!(tree.get_final_pokemon_node(), &final_pokemon_node);
+        let mut swapped_parents = tree.clone();
+        swapped_parents
+            .swap_parents(Position(1, 1))
+            .expect("Should be a pair");
+        assert_eq!(ivs(&swapped_parents, Position(2, 2)), vec![PokemonIv::HP]);
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(2, 0))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::Attack]
+            swapped_parents.pokemon_nodes[&Position(2, 2)].gender,
+            Some(PokemonGender::Female)
         );
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(2, 1))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::Speed]
+            ivs(&swapped_parents, Position(1, 1)),
+            vec![PokemonIv::HP, PokemonIv::Attack]
         );
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(2, 2))
-                .expect("Should exist")
-                .ivs,
+            swapped_parents.swap_parents(Position(2, 0)),
+            Err(PokemonBreedTreeError::InvalidPosition(Position(2, 0)))
+        );
+
+        let mut swapped_subtrees = tree.clone();
+        swapped_subtrees
+            .swap_subtrees(Position(2, 1), Position(2, 2))
+            .expect("Both are 1x31");
+        assert_eq!(
+            ivs(&swapped_subtrees, Position(1, 0)),
             vec![PokemonIv::Attack]
         );
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(2, 3))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::HP]
+            ivs(&swapped_subtrees, Position(1, 1)),
+            vec![PokemonIv::Speed, PokemonIv::HP]
         );
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(1, 0))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::Attack, PokemonIv::Speed]
+            ivs(&swapped_subtrees, Position(0, 0)),
+            vec![PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]
         );
         assert_eq!(
-            tree.pokemon_nodes
-                .get(&Position(1, 1))
-                .expect("Should exist")
-                .ivs,
-            vec![PokemonIv::Attack, PokemonIv::HP]
+            swapped_subtrees.swap_subtrees(Position(1, 1), Position(2, 0)),
+            Err(PokemonBreedTreeError::InvalidSwap(
+                Position(1, 1),
+                Position(2, 0)
+            ))
+        );
+        assert_eq!(
+            swapped_subtrees.swap_subtrees(Position(1, 0), Position(2, 0)),
+            Err(PokemonBreedTreeError::InvalidSwap(
+                Position(1, 0),
+                Position(2, 0)
+            ))
+        );
+
+        // The final pokemon can't lose the hp iv, only the pokemon bred from the leaf change
+        let mut reassigned = tree.clone();
+        assert_eq!(
+            reassigned.set_breeder_kind(Position(2, 3), PokemonBreederKind::B),
+            Err(PokemonBreedTreeError::ChangesFinalPokemon)
+        );
+        assert_eq!(reassigned.pokemon_nodes, tree.pokemon_nodes);
+        reassigned
+            .set_breeder_kind(Position(2, 2), PokemonBreederKind::C)
+            .expect("Should be a leaf");
+        assert_eq!(ivs(&reassigned, Position(1, 1)), vec![PokemonIv::HP]);
+        assert_eq!(
+            ivs(&reassigned, Position(0, 0)),
+            vec![PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP]
+        );
+        assert_eq!(
+            reassigned.set_breeder_kind(Position(1, 0), PokemonBreederKind::A),
+            Err(PokemonBreedTreeError::NotALeaf(Position(1, 0)))
+        );
+        assert_eq!(
+            reassigned.set_breeder_kind(Position(2, 0), PokemonBreederKind::D),
+            Err(PokemonBreedTreeError::UnmappedBreederKind(
+                PokemonBreederKind::D
+            ))
+        );
+    }
+
+    #[test]
+    fn pokemon_breed_tree_swap_owned_test() {
+        let charizard = charizard();
+        let final_pokemon_node = final_node(
+            charizard.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new(&final_pokemon_node, &final_iv_map);
+        let owned_male = |iv: PokemonIv| PokemonBreedTreeNode {
+            ivs: vec![iv],
+            pokemon: Some(charizard.clone()),
+            gender: Some(PokemonGender::Male),
+            nature: None,
+            evolution_note: None,
+        };
+        tree.insert_owned_pokemon(Position(2, 1), owned_male(PokemonIv::Speed));
+        tree.insert_owned_pokemon(Position(2, 3), owned_male(PokemonIv::HP));
+        assert!(tree.breed_errors.is_empty());
+
+        // Both males keep their gender, so they end up paired together
+        tree.swap_subtrees(Position(2, 1), Position(2, 2))
+            .expect("Both are 1x31");
+        assert_eq!(
+            tree.pokemon_nodes[&Position(2, 2)].gender,
+            Some(PokemonGender::Male)
+        );
+        assert_eq!(tree.breed_errors, vec![Position(2, 2), Position(2, 3)]);
+
+        tree.swap_parents(Position(1, 1)).expect("Should be a pair");
+        assert_eq!(tree.breed_errors, vec![Position(2, 2), Position(2, 3)]);
+        tree.swap_subtrees(Position(2, 1), Position(2, 3))
+            .expect("Both are 1x31");
+        let mut validated = tree.clone();
+        validated.validate();
+        assert_eq!(tree.breed_errors, validated.breed_errors);
+    }
+
+    #[test]
+    fn pokemon_breed_tree_revalidation_test() {
+        let metagross = Pokemon {
+            number: 376,
+            name: "Metagross".to_string(),
+            types: (PokemonType::Steel, Some(PokemonType::Psychic)),
+            egg_groups: (PokemonEggGroup::Genderless, None),
+            percentage_male: 0.0,
+        };
+        let final_pokemon_node = final_node(
+            metagross.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new_genderless(&final_pokemon_node, &final_iv_map)
+            .expect("Metagross is genderless");
+        tree.validate();
+        let validated = |tree: &PokemonBreedTree| {
+            let mut validated = tree.clone();
+            validated.validate();
+            validated.breed_errors
+        };
+
+        let metang_node = tree.pokemon_nodes[&Position(1, 0)].clone();
+        let mut magnemite_node = metang_node.clone();
+        magnemite_node.pokemon = Some(Pokemon {
+            number: 81,
+            name: "Magnemite".to_string(),
+            ..metagross.clone()
+        });
+        let revalidation = tree.insert_pokemon(Position(1, 0), magnemite_node);
+        // Only the edited pair, its parents and the root are checked again
+        assert_eq!(
+            revalidation.checked,
+            vec![
+                Position(0, 0),
+                Position(1, 0),
+                Position(1, 1),
+                Position(2, 0),
+                Position(2, 1)
+            ]
+        );
+        assert_eq!(
+            revalidation.new_errors,
+            vec![
+                Position(1, 0),
+                Position(1, 1),
+                Position(2, 0),
+                Position(2, 1)
+            ]
+        );
+        assert_eq!(tree.breed_errors, validated(&tree));
+
+        let revalidation = tree.insert_pokemon(Position(1, 0), metang_node);
+        assert_eq!(
+            revalidation.fixed_errors,
+            vec![
+                Position(1, 0),
+                Position(1, 1),
+                Position(2, 0),
+                Position(2, 1)
+            ]
+        );
+        assert!(tree.breed_errors.is_empty());
+
+        // Other edits keep the errors as a full validation would leave them
+        let mut costs = PokemonBreedCosts::default();
+        costs.multi_iv_breeders.insert(2, 30000);
+        tree.pokemon_nodes
+            .get_mut(&Position(2, 0))
+            .expect("Should exist")
+            .gender = Some(PokemonGender::Male);
+        tree.validate();
+        assert!(!tree.breed_errors.is_empty());
+        tree.replace_with_purchase(Position(1, 0), &costs)
+            .expect("Should be for sale");
+        assert_eq!(tree.breed_errors, validated(&tree));
+        assert!(tree.breed_errors.is_empty());
+
+        tree.pokemon_nodes
+            .get_mut(&Position(1, 0))
+            .expect("Should exist")
+            .gender = Some(PokemonGender::Male);
+        tree.validate();
+        tree.assign_genders();
+        assert_eq!(tree.breed_errors, validated(&tree));
+
+        tree.pokemon_nodes
+            .get_mut(&Position(1, 1))
+            .expect("Should exist")
+            .pokemon = Some(metagross.clone());
+        tree.validate();
+        assert!(!tree.breed_errors.is_empty());
+        tree.insert_ditto(Position(1, 1), vec![PokemonIv::Attack, PokemonIv::HP], None)
+            .expect("Should have the ivs");
+        assert!(tree.breed_errors.is_empty());
+    }
+
+    #[test]
+    fn pokemon_breed_tree_events_test() {
+        let metagross = Pokemon {
+            number: 376,
+            name: "Metagross".to_string(),
+            types: (PokemonType::Steel, Some(PokemonType::Psychic)),
+            egg_groups: (PokemonEggGroup::Genderless, None),
+            percentage_male: 0.0,
+        };
+        let final_pokemon_node = final_node(
+            metagross.clone(),
+            &[PokemonIv::Attack, PokemonIv::Speed, PokemonIv::HP],
+            None,
+        );
+        let final_iv_map = HashMap::from([
+            (PokemonBreederKind::A, PokemonIv::Attack),
+            (PokemonBreederKind::B, PokemonIv::Speed),
+            (PokemonBreederKind::C, PokemonIv::HP),
+        ]);
+        let mut tree = PokemonBreedTree::new_genderless(&final_pokemon_node, &final_iv_map)
+            .expect("Metagross is genderless");
+        tree.validate();
+        tree.take_events();
+
+        let mut magnemite_node = tree.pokemon_nodes[&Position(1, 0)].clone();
+        magnemite_node.pokemon = Some(Pokemon {
+            number: 81,
+            name: "Magnemite".to_string(),
+            ..metagross.clone()
+        });
+        tree.insert_pokemon(Position(1, 0), magnemite_node);
+        assert_eq!(
+            tree.take_events(),
+            vec![
+                PokemonBreedTreeEvent::NodeChanged(Position(1, 0)),
+                PokemonBreedTreeEvent::DiagnosticsChanged {
+                    new_errors: vec![
+                        Position(1, 0),
+                        Position(1, 1),
+                        Position(2, 0),
+                        Position(2, 1)
+                    ],
+                    fixed_errors: vec![],
+                },
+            ]
+        );
+        // Events are only made once
+        assert!(tree.take_events().is_empty());
+
+        tree.set_node_state(Position(2, 0), PokemonBreedNodeState::Acquired)
+            .expect("Should exist");
+        tree.insert_ditto(
+            Position(1, 1),
+            vec![PokemonIv::Attack, PokemonIv::HP],
+            Some(PokemonNature::Jolly),
+        )
+        .expect("Should have the ivs");
+        assert_eq!(
+            tree.take_events(),
+            vec![
+                PokemonBreedTreeEvent::NodeChanged(Position(1, 1)),
+                PokemonBreedTreeEvent::ProgressUpdated(
+                    Position(2, 0),
+                    PokemonBreedNodeState::Acquired
+                ),
+            ]
         );
     }
 }